@@ -0,0 +1,128 @@
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub peak_bytes: usize,
+    pub allocations: usize,
+    pub allocated_bytes: usize,
+}
+
+impl Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Peak heap of {} bytes, {} allocations totalling {} bytes",
+            self.peak_bytes, self.allocations, self.allocated_bytes
+        )
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use super::AllocStats;
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static CURRENT: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountingAllocator;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn record_allocation(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(size, Ordering::Relaxed);
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let pointer = System.alloc(layout);
+            if !pointer.is_null() {
+                record_allocation(layout.size());
+            }
+            pointer
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let pointer = System.alloc_zeroed(layout);
+            if !pointer.is_null() {
+                record_allocation(layout.size());
+            }
+            pointer
+        }
+
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            System.dealloc(pointer, layout);
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_pointer = System.realloc(pointer, layout, new_size);
+            if !new_pointer.is_null() {
+                CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+                record_allocation(new_size);
+            }
+            new_pointer
+        }
+    }
+
+    pub(super) fn start() -> AllocStats {
+        PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+        snapshot()
+    }
+
+    pub(super) fn snapshot() -> AllocStats {
+        AllocStats {
+            peak_bytes: PEAK.load(Ordering::Relaxed),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes: ALLOCATED.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+pub fn measure_allocations<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    let start = counting::start();
+    let result = f();
+    let end = counting::snapshot();
+
+    (
+        result,
+        Some(AllocStats {
+            peak_bytes: end.peak_bytes,
+            allocations: end.allocations - start.allocations,
+            allocated_bytes: end.allocated_bytes - start.allocated_bytes,
+        }),
+    )
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+pub fn measure_allocations<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    (f(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_allocations_reports_with_the_feature_enabled() {
+        let (length, stats) = measure_allocations(|| vec![0u8; 1024].len());
+
+        assert_eq!(length, 1024);
+        assert_eq!(stats.is_some(), cfg!(feature = "alloc-stats"));
+        stats.iter().for_each(|stats| {
+            assert!(stats.allocations >= 1);
+            assert!(stats.allocated_bytes >= 1024);
+            assert!(stats.peak_bytes >= 1024);
+        });
+    }
+}
@@ -0,0 +1,342 @@
+use ansi_to_tui::IntoText;
+use anyhow::{anyhow, Result};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    text::Text,
+    widgets::{Block, Borders, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::{
+    cell::{Cell, RefCell},
+    fs,
+    io::{stdout, IsTerminal},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+const TICK: Duration = Duration::from_millis(10);
+const MAX_FPS: u32 = 120;
+const MAX_FRAMES: usize = 2_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationFrame {
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub fps: u32,
+    pub export: Option<PathBuf>,
+    playback: bool,
+    recorded: Cell<usize>,
+    stride: Cell<usize>,
+    frames: RefCell<Vec<AnimationFrame>>,
+    latest: RefCell<Option<AnimationFrame>>,
+    error: RefCell<Option<String>>,
+}
+
+impl Animation {
+    pub fn new(fps: u32, export: Option<PathBuf>) -> Animation {
+        Animation::with_playback(fps, export, stdout().is_terminal())
+    }
+
+    fn with_playback(fps: u32, export: Option<PathBuf>, playback: bool) -> Animation {
+        Animation {
+            fps,
+            export,
+            playback,
+            recorded: Cell::new(0),
+            stride: Cell::new(1),
+            frames: RefCell::new(Vec::new()),
+            latest: RefCell::new(None),
+            error: RefCell::new(None),
+        }
+    }
+
+    pub fn record(&self, title: &str, body: String) {
+        let frame = AnimationFrame {
+            title: title.to_string(),
+            body,
+        };
+        let index = self.recorded.get();
+        self.recorded.set(index + 1);
+
+        match &self.export {
+            Some(directory) => {
+                if self.error.borrow().is_none() {
+                    if let Err(error) = export_frame(directory, index, &frame) {
+                        self.error.replace(Some(format!(
+                            "Could not export frame {} to {}: {}",
+                            index,
+                            directory.display(),
+                            error
+                        )));
+                    }
+                }
+            }
+            None if self.playback => self.buffer(index, frame),
+            None => print_frame(&frame),
+        }
+    }
+
+    // Playback keeps the frames in memory, so once the buffer is full every other frame is
+    // dropped and only every `stride`th frame is kept from then on.
+    fn buffer(&self, index: usize, frame: AnimationFrame) {
+        if !index.is_multiple_of(self.stride.get()) {
+            self.latest.replace(Some(frame));
+            return;
+        }
+
+        let mut frames = self.frames.borrow_mut();
+        frames.push(frame);
+        self.latest.replace(None);
+        if frames.len() > MAX_FRAMES {
+            let kept = frames.drain(..).step_by(2).collect();
+            *frames = kept;
+            self.stride.set(self.stride.get() * 2);
+        }
+    }
+
+    pub fn frames(&self) -> Vec<AnimationFrame> {
+        self.frames
+            .borrow()
+            .iter()
+            .chain(self.latest.borrow().iter())
+            .cloned()
+            .collect()
+    }
+
+    pub fn finish(&self) -> Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(anyhow!(error));
+        }
+
+        match &self.export {
+            Some(directory) => {
+                println!(
+                    "Exported {} frames to {}",
+                    self.recorded.get(),
+                    directory.display()
+                );
+                Ok(())
+            }
+            None if self.playback => {
+                let mut terminal = ratatui::init();
+                let result = Player::new(self.frames(), self.fps).run(&mut terminal);
+                ratatui::restore();
+                result
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn export_frame(directory: &Path, index: usize, frame: &AnimationFrame) -> std::io::Result<()> {
+    if index == 0 {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(
+        directory.join(format!("frame{:05}.txt", index)),
+        format!("{}\n{}\n", frame.title, frame.body),
+    )
+}
+
+fn print_frame(frame: &AnimationFrame) {
+    println!("{}", frame.title);
+    println!("{}", frame.body);
+    println!();
+}
+
+struct Player {
+    frames: Vec<AnimationFrame>,
+    index: usize,
+    fps: u32,
+    paused: bool,
+    last_step: Instant,
+}
+
+impl Player {
+    fn new(frames: Vec<AnimationFrame>, fps: u32) -> Player {
+        Player {
+            frames,
+            index: 0,
+            fps: fps.clamp(1, MAX_FPS),
+            paused: false,
+            last_step: Instant::now(),
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                        return Ok(());
+                    }
+                }
+            }
+
+            self.tick();
+        }
+    }
+
+    fn last_index(&self) -> usize {
+        self.frames.len().saturating_sub(1)
+    }
+
+    fn tick(&mut self) {
+        let frame_time = Duration::from_secs(1) / self.fps;
+        if !self.paused && self.last_step.elapsed() >= frame_time {
+            self.last_step = Instant::now();
+            if self.index < self.last_index() {
+                self.index += 1;
+            } else {
+                self.paused = true;
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char(' ') => {
+                if self.paused && self.index == self.last_index() {
+                    self.index = 0;
+                }
+                self.paused = !self.paused;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.paused = true;
+                self.index = (self.index + 1).min(self.last_index());
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.paused = true;
+                self.index = self.index.saturating_sub(1);
+            }
+            KeyCode::Home => self.index = 0,
+            KeyCode::End => self.index = self.last_index(),
+            KeyCode::Char('+') | KeyCode::Up => self.fps = (self.fps * 2).min(MAX_FPS),
+            KeyCode::Char('-') | KeyCode::Down => self.fps = (self.fps / 2).max(1),
+            _ => (),
+        }
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let (title, text) = match self.frames.get(self.index) {
+            Some(current) => (
+                current.title.clone(),
+                current
+                    .body
+                    .into_text()
+                    .unwrap_or_else(|_| Text::raw(current.body.clone())),
+            ),
+            None => ("No frames".to_string(), Text::default()),
+        };
+        frame.render_widget(
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title)),
+            body,
+        );
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Frame {}/{} at {} fps{}  space play/pause  ←/→ step  +/- speed  q quit",
+                (self.index + 1).min(self.frames.len()),
+                self.frames.len(),
+                self.fps,
+                if self.paused { " (paused)" } else { "" }
+            )),
+            status,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::env;
+
+    fn frames() -> Vec<AnimationFrame> {
+        (0..3)
+            .map(|step| AnimationFrame {
+                title: format!("Step {}", step),
+                body: format!("#{}#", step),
+            })
+            .collect()
+    }
+
+    fn render(player: &Player) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 6)).unwrap();
+        terminal.draw(|frame| player.draw(frame)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn player_steps_and_pauses_through_frames() {
+        let mut player = Player::new(frames(), 10);
+        assert!(render(&player).contains("#0#"));
+
+        player.handle_key(KeyCode::Right);
+        player.handle_key(KeyCode::Right);
+        player.handle_key(KeyCode::Right);
+        assert!(player.paused);
+        assert!(render(&player).contains("Frame 3/3 at 10 fps (paused)"));
+
+        player.handle_key(KeyCode::Left);
+        player.handle_key(KeyCode::Char('+'));
+        assert!(render(&player).contains("Step 1"));
+        assert!(render(&player).contains("20 fps"));
+
+        player.handle_key(KeyCode::Char(' '));
+        player.last_step = Instant::now() - Duration::from_secs(1);
+        player.tick();
+        assert_eq!(player.index, 2);
+        player.last_step = Instant::now() - Duration::from_secs(1);
+        player.tick();
+        assert!(player.paused);
+        assert!(!player.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn animation_exports_frames_to_a_directory() {
+        let directory = env::temp_dir().join(format!("animation-{}", std::process::id()));
+        let animation = Animation::new(10, Some(directory.clone()));
+        frames()
+            .into_iter()
+            .for_each(|frame| animation.record(&frame.title, frame.body));
+
+        animation.finish().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(directory.join("frame00001.txt")).unwrap(),
+            "Step 1\n#1#\n"
+        );
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 3);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn playback_keeps_a_bounded_sample_of_frames() {
+        let animation = Animation::with_playback(10, None, true);
+        (0..=5_001).for_each(|step| animation.record(&format!("Step {}", step), String::new()));
+        let frames = animation.frames();
+
+        assert!(frames.len() <= MAX_FRAMES + 1);
+        assert_eq!(frames[0].title, "Step 0");
+        assert_eq!(frames[1].title, "Step 4");
+        assert_eq!(frames.last().unwrap().title, "Step 5001");
+    }
+}
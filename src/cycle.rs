@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Result};
+use std::{collections::HashMap, hash::Hash};
+use tracing::debug;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: usize,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct CycleRun<S> {
+    pub cycle: Option<Cycle>,
+    pub state: S,
+    metrics: Vec<usize>,
+}
+
+impl<S> CycleRun<S> {
+    pub fn metric_at(&self, steps: usize) -> Result<usize> {
+        if let Some(metric) = self.metrics.get(steps) {
+            return Ok(*metric);
+        }
+
+        let cycle = self.cycle.ok_or_else(|| {
+            anyhow!(
+                "No cycle found within {} steps",
+                self.metrics.len().saturating_sub(1)
+            )
+        })?;
+        let cycle_start = *self.metrics.get(cycle.offset).expect("Offset recorded");
+        let cycle_end = *self
+            .metrics
+            .get(cycle.offset + cycle.period)
+            .expect("Cycle recorded");
+        let growth = cycle_end.checked_sub(cycle_start).ok_or_else(|| {
+            anyhow!(
+                "The metric decreases from {} to {} over the cycle",
+                cycle_start,
+                cycle_end
+            )
+        })?;
+        let cycles = (steps - cycle.offset) / cycle.period;
+        let remainder = (steps - cycle.offset) % cycle.period;
+        let partial = *self
+            .metrics
+            .get(cycle.offset + remainder)
+            .expect("Remainder recorded");
+
+        cycles
+            .checked_mul(growth)
+            .and_then(|growth| growth.checked_add(partial))
+            .ok_or_else(|| anyhow!("The metric after {} steps overflows", steps))
+    }
+}
+
+pub fn find_cycle<S, K>(
+    mut state: S,
+    max_steps: usize,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    mut metric: impl FnMut(&S) -> usize,
+) -> CycleRun<S>
+where
+    K: Eq + Hash,
+{
+    let mut seen = HashMap::from([(key(&state), 0usize)]);
+    let mut metrics = vec![metric(&state)];
+
+    for steps in 1..=max_steps {
+        step(&mut state);
+        metrics.push(metric(&state));
+
+        if let Some(offset) = seen.insert(key(&state), steps) {
+            debug!(offset, period = steps - offset, "Found cycle");
+            return CycleRun {
+                cycle: Some(Cycle {
+                    offset,
+                    period: steps - offset,
+                }),
+                state,
+                metrics,
+            };
+        }
+    }
+
+    CycleRun {
+        cycle: None,
+        state,
+        metrics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(
+        max_steps: usize,
+        metric: impl FnMut(&(usize, usize)) -> usize,
+    ) -> CycleRun<(usize, usize)> {
+        find_cycle(
+            (0, 0),
+            max_steps,
+            |(position, total)| {
+                *position = if *position == 4 { 2 } else { *position + 1 };
+                *total += *position;
+            },
+            |(position, _)| *position,
+            metric,
+        )
+    }
+
+    #[test]
+    fn metric_at_extrapolates_past_the_cycle() {
+        let cycle_run = run(100, |(_, total)| *total);
+
+        assert_eq!(
+            cycle_run.cycle,
+            Some(Cycle {
+                offset: 2,
+                period: 3
+            })
+        );
+        assert_eq!(cycle_run.metric_at(1).unwrap(), 1);
+        assert_eq!(cycle_run.metric_at(2).unwrap(), 3);
+        assert_eq!(cycle_run.metric_at(8).unwrap(), 21);
+        assert_eq!(cycle_run.metric_at(10).unwrap(), 28);
+    }
+
+    #[test]
+    fn metric_at_rejects_unknown_and_decreasing_metrics() {
+        let decreasing = run(100, |(_, total)| 100 - total);
+        assert_eq!(decreasing.metric_at(3).unwrap(), 94);
+        assert!(decreasing.metric_at(20).is_err());
+
+        let short = run(3, |(_, total)| *total);
+        assert_eq!(short.cycle, None);
+        assert_eq!(short.metric_at(3).unwrap(), 6);
+        assert!(short.metric_at(10).is_err());
+        assert!(run(100, |(_, total)| *total).metric_at(usize::MAX).is_err());
+    }
+}
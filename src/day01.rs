@@ -1,35 +1,48 @@
 use adventofcode2022::{
-    parse_between_blank_lines, parse_lines, parse_usize, single_arg, Command, ParseError, Problem,
+    parse_between_blank_lines, parse_lines, parse_usize, Command, CommandResult, ParseError,
+    Problem,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{prelude::Simple, primitive::end, Parser};
-use clap::ArgMatches;
-use std::cell::LazyCell;
+use clap::Args;
+use itertools::Itertools;
+use std::{cell::LazyCell, cmp::Reverse, collections::BinaryHeap, io::BufRead, iter::once};
 
 type ParseOutput = Vec<Vec<usize>>;
 
 pub const DAY_01: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let number = single_arg("number", 'n', "The number of elves to sum")
-        .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day01",
         "Takes a list of elves backpacks calorie count and find the ones with the most",
         "Path to the input file. Input should be newline delimited groups integers. Each group represents one elf's bag, each line in the group is the caloric value of that item.",
-    vec![number], parse_arguments, parse_file, run)
-        .with_part1(CommandLineArguments { n: 1 }, "Finds the elf with the most calories in their bag and returns the sum of the calories")
-        .with_part2(CommandLineArguments { n: 3 }, "Finds the elves with the 3 top most calories and sums the calories.");
+    parse_file, run)
+        .with_stream(run_stream)
+        .with_part1(CommandLineArguments { n: 1, report: false }, "Finds the elf with the most calories in their bag and returns the sum of the calories")
+        .with_part2(CommandLineArguments { n: 3, report: false }, "Finds the elves with the 3 top most calories and sums the calories.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(
+        short,
+        long = "number",
+        value_name = "NUMBER",
+        help = "The number of elves to sum"
+    )]
     n: usize,
+    #[arg(
+        short,
+        long,
+        help = "Reports which elves made the top and what they carry instead of the total"
+    )]
+    report: bool,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        n: *args.get_one::<usize>("number").expect("Valid arguments"),
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Elf {
+    index: usize,
+    calories: usize,
 }
 
 fn parse_file(file: String) -> Result<ParseOutput> {
@@ -39,17 +52,229 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_between_blank_lines(parse_lines(parse_usize())).then_ignore(end())
+    let bag = parse_lines(parse_usize())
+        .then_ignore(end())
+        .try_map(|bag, span| match bag.is_empty() {
+            true => Err(Simple::custom(span, "A bag must hold at least one item")),
+            false => Ok(bag),
+        });
+    parse_between_blank_lines(bag).then_ignore(end())
+}
+
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<CommandResult> {
+    let bag_sums = input.into_iter().enumerate().map(|(index, bag)| {
+        bag.into_iter()
+            .try_fold(0usize, |total, calories| total.checked_add(calories))
+            .ok_or_else(|| anyhow!("Elf {} carries too many calories to add up", index + 1))
+    });
+
+    summarize(top_elves(bag_sums, arguments.n)?, &arguments)
+}
+
+fn run_stream(reader: Box<dyn BufRead>, arguments: CommandLineArguments) -> Result<CommandResult> {
+    summarize(top_elves(stream_bag_sums(reader), arguments.n)?, &arguments)
+}
+
+fn parse_calories(line: &str, number: usize) -> Result<usize> {
+    parse_usize()
+        .then_ignore(end())
+        .parse(line)
+        .map_err(|_| anyhow!("Line {} has an invalid calorie count: {}", number, line))
+}
+
+// Accepts exactly what `parse_file` does: one canonical number per line, and bags separated by a
+// single blank line with no blank line before the first bag or after the last.
+fn stream_bag_sums(reader: impl BufRead) -> impl Iterator<Item = Result<usize>> {
+    reader
+        .lines()
+        .map(Some)
+        .chain(once(None))
+        .enumerate()
+        .scan(None, |bag: &mut Option<usize>, (index, line)| {
+            let number = index + 1;
+            Some(match line {
+                None => Some(
+                    bag.take()
+                        .ok_or_else(|| anyhow!("The input does not end with a bag")),
+                ),
+                Some(Err(error)) => Some(Err(error.into())),
+                Some(Ok(line)) if line.is_empty() => Some(
+                    bag.take()
+                        .ok_or_else(|| anyhow!("Line {} is a blank line outside of a bag", number)),
+                ),
+                Some(Ok(line)) => parse_calories(&line, number)
+                    .and_then(|calories| {
+                        bag.unwrap_or(0).checked_add(calories).ok_or_else(|| {
+                            anyhow!("Line {} adds too many calories to its bag", number)
+                        })
+                    })
+                    .map(|total| *bag = Some(total))
+                    .err()
+                    .map(Err),
+            })
+        })
+        .flatten()
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    let mut bag_sums = input
+fn top_elves(bag_sums: impl Iterator<Item = Result<usize>>, n: usize) -> Result<Vec<Elf>> {
+    let mut heap = BinaryHeap::new();
+
+    for (index, calories) in bag_sums.enumerate() {
+        heap.push(Reverse((calories?, Reverse(index + 1))));
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+
+    Ok(heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((calories, Reverse(index)))| Elf { index, calories })
+        .collect())
+}
+
+fn summarize(elves: Vec<Elf>, arguments: &CommandLineArguments) -> Result<CommandResult> {
+    let total = elves
+        .iter()
+        .try_fold(0usize, |total, elf| total.checked_add(elf.calories))
+        .ok_or_else(|| {
+            anyhow!(
+                "The top {} elves carry too many calories to add up",
+                elves.len()
+            )
+        })?;
+
+    Ok(if arguments.report {
+        elves
+            .iter()
+            .map(|elf| format!("Elf {} carries {} calories", elf.index, elf.calories))
+            .chain(once(format!("Total of {} calories", total)))
+            .join("\n")
+            .into()
+    } else {
+        total.into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{property, Rng};
+    use itertools::Itertools;
+
+    const SAMPLE: &str = include_str!("../day01/sample.txt");
+
+    fn print_input(input: &ParseOutput) -> String {
+        input.iter().map(|bag| bag.iter().join("\n")).join("\n\n")
+    }
+
+    fn generate_input(rng: &mut Rng, size: usize) -> ParseOutput {
+        (0..rng.usize_in(1..=size))
+            .map(|_| {
+                (0..rng.usize_in(1..=size))
+                    .map(|_| rng.usize_in(1..=100_000))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn sums(bags: &ParseOutput) -> Vec<usize> {
+        bags.iter().map(|bag| bag.iter().sum()).collect()
+    }
+
+    #[test]
+    fn top_elves_accepts_counts_larger_than_the_elves() {
+        let bags = parse_file(SAMPLE.to_string()).expect("Sample parses");
+
+        [1_000_000_000_000, usize::MAX].into_iter().for_each(|n| {
+            let elves = top_elves(sums(&bags).into_iter().map(Ok), n).unwrap();
+            assert_eq!(elves.len(), bags.len());
+        });
+    }
+
+    #[test]
+    fn top_elves_reports_the_largest_bags_in_order() {
+        let bags = parse_file(SAMPLE.to_string()).expect("Sample parses");
+        let elves = top_elves(sums(&bags).into_iter().map(Ok), 3).unwrap();
+
+        assert_eq!(
+            elves,
+            vec![
+                Elf {
+                    index: 4,
+                    calories: 24000
+                },
+                Elf {
+                    index: 3,
+                    calories: 11000
+                },
+                Elf {
+                    index: 5,
+                    calories: 10000
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn streamed_bags_match_the_parsed_bags() {
+        property(100, 20, |rng, size| {
+            let input = generate_input(rng, size);
+            let streamed = stream_bag_sums(print_input(&input).as_bytes())
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(streamed, sums(&input));
+        });
+    }
+
+    #[test]
+    fn streamed_bags_accept_what_the_parser_accepts() {
+        [
+            "1\n2\n\n3\n",
+            "1\r\n\r\n2",
+            "1\n\n\n3",
+            "\n1",
+            "1\n\n",
+            "",
+            " 1",
+            "01",
+            "+1",
+            "1\n \n2",
+        ]
         .into_iter()
-        .map(|bag| bag.into_iter().sum())
-        .collect::<Vec<usize>>();
+        .for_each(|input| {
+            let streamed = stream_bag_sums(input.as_bytes()).collect::<Result<Vec<_>>>();
+            assert_eq!(
+                streamed.is_ok(),
+                parse_file(input.to_string()).is_ok(),
+                "{:?}",
+                input
+            );
+        });
+    }
+
+    #[test]
+    fn streamed_bags_report_overflowing_lines() {
+        let input = format!("1\n\n{}\n1\n", usize::MAX);
+        let error = stream_bag_sums(input.as_bytes())
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
 
-    bag_sums.sort();
-    bag_sums.reverse();
+        assert_eq!(
+            error.to_string(),
+            "Line 4 adds too many calories to its bag"
+        );
+    }
 
-    bag_sums.into_iter().take(arguments.n).sum()
+    #[test]
+    fn streamed_bags_report_invalid_lines() {
+        let error = stream_bag_sums("100\n\nlots\n".as_bytes())
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Line 3 has an invalid calorie count: lots"
+        );
+    }
 }
@@ -1,95 +1,152 @@
-use std::cell::LazyCell;
+use std::{
+    cell::LazyCell,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
-use adventofcode2022::{parse_lines, single_arg, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{
+    file_to_string, parse_lines, parse_usize, Command, CommandResult, ParseError, Problem,
+};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
-    primitive::{end, just},
-    Parser,
+    primitive::{end, filter, just},
+    text, Parser,
 };
-use clap::{builder::EnumValueParser, ArgMatches, ValueEnum};
-
-#[derive(Debug, Clone)]
-pub enum Roshambo {
-    Rock,
-    Paper,
-    Scissors,
-}
+use clap::{Args, ValueEnum, ValueHint};
+use itertools::Itertools;
 
-impl Roshambo {
-    fn get_score(&self) -> usize {
-        match self {
-            Roshambo::Rock => 1,
-            Roshambo::Paper => 2,
-            Roshambo::Scissors => 3,
-        }
-    }
+const ROCK_PAPER_SCISSORS: &str = include_str!("../day02/rock_paper_scissors.txt");
 
-    fn get_outcome(&self, opponents_move: &Roshambo) -> Outcome {
-        match (opponents_move, self) {
-            (Roshambo::Rock, Roshambo::Paper) => Outcome::Win,
-            (Roshambo::Rock, Roshambo::Scissors) => Outcome::Lose,
-            (Roshambo::Paper, Roshambo::Rock) => Outcome::Lose,
-            (Roshambo::Paper, Roshambo::Scissors) => Outcome::Win,
-            (Roshambo::Scissors, Roshambo::Rock) => Outcome::Win,
-            (Roshambo::Scissors, Roshambo::Paper) => Outcome::Lose,
-            _ => Outcome::Draw,
-        }
-    }
-}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move(usize);
 
-#[derive(Debug, Clone)]
-enum Outcome {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
     Win,
     Lose,
     Draw,
 }
 
 impl Outcome {
-    fn get_score(&self) -> usize {
-        match self {
-            Outcome::Win => 6,
-            Outcome::Lose => 0,
-            Outcome::Draw => 3,
+    fn from_name(name: &str) -> Option<Outcome> {
+        match name.to_ascii_lowercase().as_str() {
+            "win" => Some(Outcome::Win),
+            "lose" => Some(Outcome::Lose),
+            "draw" => Some(Outcome::Draw),
+            _ => None,
         }
     }
 
-    fn get_roshambo(&self, opponents_move: &Roshambo) -> Roshambo {
-        match (opponents_move, self) {
-            (Roshambo::Rock, Outcome::Win) => Roshambo::Paper,
-            (Roshambo::Rock, Outcome::Lose) => Roshambo::Scissors,
-            (Roshambo::Paper, Outcome::Win) => Roshambo::Scissors,
-            (Roshambo::Paper, Outcome::Lose) => Roshambo::Rock,
-            (Roshambo::Scissors, Outcome::Win) => Roshambo::Rock,
-            (Roshambo::Scissors, Outcome::Lose) => Roshambo::Paper,
-            (a, Outcome::Draw) => a.clone(),
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Win => "win",
+            Outcome::Lose => "lose",
+            Outcome::Draw => "draw",
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum StrategyKey {
-    X,
-    Y,
-    Z,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StrategyKey(char);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GameMove {
+    name: String,
+    score: usize,
+    symbol: char,
 }
 
-impl StrategyKey {
-    fn to_roshambo(&self) -> Roshambo {
-        match self {
-            StrategyKey::X => Roshambo::Rock,
-            StrategyKey::Y => Roshambo::Paper,
-            StrategyKey::Z => Roshambo::Scissors,
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    moves: Vec<GameMove>,
+    beats: HashSet<(Move, Move)>,
+    outcome_scores: HashMap<Outcome, usize>,
+    move_keys: HashMap<StrategyKey, Move>,
+    outcome_keys: HashMap<StrategyKey, Outcome>,
+}
+
+impl Game {
+    fn opponent_move(&self, symbol: char) -> Result<Move> {
+        self.moves
+            .iter()
+            .position(|game_move| game_move.symbol == symbol)
+            .map(Move)
+            .ok_or_else(|| anyhow!("{} is not an opponent move in this game", symbol))
     }
 
-    fn to_outcome(&self) -> Outcome {
-        match self {
-            StrategyKey::X => Outcome::Lose,
-            StrategyKey::Y => Outcome::Draw,
-            StrategyKey::Z => Outcome::Win,
+    fn find_move(&self, name: &str) -> Option<Move> {
+        self.moves
+            .iter()
+            .position(|game_move| game_move.name.eq_ignore_ascii_case(name))
+            .map(Move)
+    }
+
+    fn move_name(&self, game_move: &Move) -> &str {
+        &self.moves[game_move.0].name
+    }
+
+    fn move_score(&self, game_move: Move) -> usize {
+        self.moves[game_move.0].score
+    }
+
+    fn outcome_score(&self, outcome: &Outcome) -> usize {
+        self.outcome_scores[outcome]
+    }
+
+    fn get_outcome(&self, our_move: Move, opponents_move: Move) -> Outcome {
+        if self.beats.contains(&(our_move, opponents_move)) {
+            Outcome::Win
+        } else if self.beats.contains(&(opponents_move, our_move)) {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
         }
     }
+
+    fn get_move(&self, opponents_move: Move, outcome: &Outcome) -> Option<Move> {
+        self.all_moves()
+            .into_iter()
+            .filter(|our_move| self.get_outcome(*our_move, opponents_move) == *outcome)
+            // Several moves can give the same outcome, so prefer the highest scoring one and
+            // then the one defined first.
+            .min_by_key(|our_move| (Reverse(self.move_score(*our_move)), our_move.0))
+    }
+
+    fn all_moves(&self) -> Vec<Move> {
+        (0..self.moves.len()).map(Move).collect()
+    }
+
+    fn move_mapping(&self, overrides: &[String]) -> Result<HashMap<StrategyKey, Move>> {
+        override_mapping(self.move_keys.clone(), overrides, |name| {
+            self.find_move(name)
+        })
+    }
+
+    fn outcome_mapping(&self, overrides: &[String]) -> Result<HashMap<StrategyKey, Outcome>> {
+        override_mapping(self.outcome_keys.clone(), overrides, Outcome::from_name)
+    }
+}
+
+fn override_mapping<T>(
+    mut mapping: HashMap<StrategyKey, T>,
+    overrides: &[String],
+    resolve: impl Fn(&str) -> Option<T>,
+) -> Result<HashMap<StrategyKey, T>> {
+    for entry in overrides {
+        let (key, name) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Mapping {} should look like KEY=NAME", entry))?;
+        let key = key
+            .chars()
+            .exactly_one()
+            .map_err(|_| anyhow!("Strategy key {} should be a single character", key))?;
+        let value = resolve(name).ok_or_else(|| anyhow!("{} is not part of this game", name))?;
+        mapping.insert(StrategyKey(key), value);
+    }
+
+    Ok(mapping)
 }
 
 #[derive(Debug, ValueEnum, Clone)]
@@ -99,46 +156,74 @@ pub enum Strategy {
 }
 
 trait Round {
-    fn get_round_result(&self) -> usize;
+    fn get_round_result(&self, game: &Game) -> Result<usize>;
 }
 
-impl Round for (Roshambo, Roshambo) {
-    fn get_round_result(&self) -> usize {
-        self.1.get_outcome(&self.0).get_score() + self.1.get_score()
+impl Round for (Move, Move) {
+    fn get_round_result(&self, game: &Game) -> Result<usize> {
+        Ok(game.outcome_score(&game.get_outcome(self.1, self.0)) + game.move_score(self.1))
     }
 }
 
-impl Round for (Roshambo, Outcome) {
-    fn get_round_result(&self) -> usize {
-        self.1.get_roshambo(&self.0).get_score() + self.1.get_score()
+impl Round for (Move, Outcome) {
+    fn get_round_result(&self, game: &Game) -> Result<usize> {
+        let our_move = game.get_move(self.0, &self.1).ok_or_else(|| {
+            anyhow!(
+                "No move can {:?} against {}",
+                self.1,
+                game.move_name(&self.0)
+            )
+        })?;
+        Ok(game.move_score(our_move) + game.outcome_score(&self.1))
     }
 }
 
-type ParseOutput = Vec<(Roshambo, StrategyKey)>;
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GameEntry {
+    Move(GameMove, StrategyKey),
+    Beats(String, String),
+    Outcome(Outcome, usize, StrategyKey),
+}
+
+type ParseOutput = Vec<(char, StrategyKey)>;
 
 pub const DAY_02: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let strategy = single_arg("strategy", 's', "The strategy to use in the game")
-        .value_parser(EnumValueParser::<Strategy>::new());
     let problem = Problem::new(
         "day02",
         "Parses and scores a secret strategy for a rock paper scissors tournament",
-        "Path to the input file. Input should be lines with value A, B, or C followed by X, Y, or Z separated by a space. The first character represents the opponents move and the second is our strategy",
-    vec![strategy], parse_arguments, parse_file, run)
-        .with_part1(CommandLineArguments { strategy: Strategy::Roshambo }, "Our strategy is a certain value of Rock, Paper, or Scissors to use")
-        .with_part2(CommandLineArguments { strategy: Strategy::Outcome }, "Our strategy is a target outcome to have");
+        "Path to the input file. Input should be lines with an opponent's move symbol followed by a strategy key separated by a space, A, B, or C and X, Y, or Z for rock paper scissors. The first character represents the opponents move and the second is our strategy",
+    parse_file, run)
+        .with_part1(CommandLineArguments { strategy: Strategy::Roshambo, game: None, mapping: Vec::new(), search: false }, "Our strategy is a certain value of Rock, Paper, or Scissors to use")
+        .with_part2(CommandLineArguments { strategy: Strategy::Outcome, game: None, mapping: Vec::new(), search: false }, "Our strategy is a target outcome to have");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The strategy to use in the game")]
     strategy: Strategy,
-}
-
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    let strategy = args.get_one::<Strategy>("strategy").expect("Required arg");
-    CommandLineArguments {
-        strategy: strategy.clone(),
-    }
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        help = "Path to a game definition with its moves, what beats what and the outcome scores. Defaults to rock paper scissors"
+    )]
+    game: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        value_name = "KEY=NAME",
+        value_delimiter = ',',
+        help = "Overrides what strategy keys mean, e.g. X=Paper for the roshambo strategy or X=win for the outcome strategy"
+    )]
+    mapping: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with = "mapping",
+        help = "Tries every mapping of strategy keys and reports the best and worst totals with the mapping that achieves them"
+    )]
+    search: bool,
 }
 
 fn parse_file(file: String) -> Result<ParseOutput> {
@@ -148,39 +233,424 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_game()).then_ignore(end())
+    parse_lines(parse_round()).then_ignore(end())
+}
+
+fn parse_round() -> impl Parser<char, (char, StrategyKey), Error = Simple<char>> {
+    parse_symbol().then_ignore(just(' ')).then(parse_strategy())
+}
+
+fn parse_symbol() -> impl Parser<char, char, Error = Simple<char>> {
+    filter(|c: &char| c.is_ascii_alphanumeric())
+}
+
+fn parse_strategy() -> impl Parser<char, StrategyKey, Error = Simple<char>> {
+    parse_symbol().map(StrategyKey)
+}
+
+fn parse_game_file(file: String) -> Result<Game> {
+    game_parser()
+        .parse(file.clone())
+        .map_err(|e| ParseError(file, e).into())
+        .and_then(build_game)
 }
 
-fn parse_game() -> impl Parser<char, (Roshambo, StrategyKey), Error = Simple<char>> {
-    parse_roshambo()
-        .then_ignore(just(' '))
+fn game_parser() -> impl Parser<char, Vec<GameEntry>, Error = Simple<char>> {
+    let comment = just('#')
+        .then(filter(|c: &char| *c != '\n').repeated())
+        .to(None);
+    let line = comment.or(parse_game_entry().map(Some)).or_not();
+
+    parse_lines(line)
+        .then_ignore(end())
+        .map(|entries| entries.into_iter().flatten().flatten().collect())
+}
+
+fn parse_game_entry() -> impl Parser<char, GameEntry, Error = Simple<char>> {
+    let space = || just(' ').repeated().at_least(1);
+    let game_move = just("move")
+        .ignore_then(space())
+        .ignore_then(text::ident())
+        .then_ignore(space())
+        .then(parse_usize())
+        .then_ignore(space())
+        .then(parse_symbol())
+        .then_ignore(space())
+        .then(parse_strategy())
+        .map(|(((name, score), symbol), key)| {
+            GameEntry::Move(
+                GameMove {
+                    name,
+                    score,
+                    symbol,
+                },
+                key,
+            )
+        });
+    let beats = just("beats")
+        .ignore_then(space())
+        .ignore_then(text::ident())
+        .then_ignore(space())
+        .then(text::ident())
+        .map(|(winner, loser)| GameEntry::Beats(winner, loser));
+    let outcome_name = just("lose")
+        .to(Outcome::Lose)
+        .or(just("draw").to(Outcome::Draw))
+        .or(just("win").to(Outcome::Win));
+    let outcome = just("outcome")
+        .ignore_then(space())
+        .ignore_then(outcome_name)
+        .then_ignore(space())
+        .then(parse_usize())
+        .then_ignore(space())
         .then(parse_strategy())
+        .map(|((outcome, score), key)| GameEntry::Outcome(outcome, score, key));
+
+    game_move.or(beats).or(outcome)
 }
 
-fn parse_roshambo() -> impl Parser<char, Roshambo, Error = Simple<char>> {
-    let rock = just('A').to(Roshambo::Rock);
-    let paper = just('B').to(Roshambo::Paper);
-    let scissors = just('C').to(Roshambo::Scissors);
+fn build_game(entries: Vec<GameEntry>) -> Result<Game> {
+    let mut game = Game {
+        moves: Vec::new(),
+        beats: HashSet::new(),
+        outcome_scores: HashMap::new(),
+        move_keys: HashMap::new(),
+        outcome_keys: HashMap::new(),
+    };
 
-    rock.or(paper).or(scissors)
+    for entry in entries.iter() {
+        if let GameEntry::Move(game_move, key) = entry {
+            if game.find_move(&game_move.name).is_some() {
+                return Err(anyhow!("Move {} is defined twice", game_move.name));
+            }
+            if game.opponent_move(game_move.symbol).is_ok() {
+                return Err(anyhow!(
+                    "Opponent symbol {} is used twice",
+                    game_move.symbol
+                ));
+            }
+            if game.move_keys.contains_key(key) {
+                return Err(anyhow!("Strategy key {} is used by two moves", key.0));
+            }
+            game.move_keys.insert(*key, Move(game.moves.len()));
+            game.moves.push(game_move.clone());
+        }
+    }
+
+    for entry in entries.iter() {
+        match entry {
+            GameEntry::Beats(winner, loser) => {
+                let find = |name: &String| {
+                    game.find_move(name)
+                        .ok_or_else(|| anyhow!("{} is not a move in this game", name))
+                };
+                let (winner_move, loser_move) = (find(winner)?, find(loser)?);
+                if winner_move == loser_move {
+                    return Err(anyhow!("{} cannot beat itself", winner));
+                }
+                if game.beats.contains(&(loser_move, winner_move)) {
+                    return Err(anyhow!("{} and {} cannot beat each other", winner, loser));
+                }
+                if !game.beats.insert((winner_move, loser_move)) {
+                    return Err(anyhow!("{} beats {} twice", winner, loser));
+                }
+            }
+            GameEntry::Outcome(outcome, score, key) => {
+                if game.outcome_scores.insert(*outcome, *score).is_some() {
+                    return Err(anyhow!("Outcome {:?} is scored twice", outcome));
+                }
+                if game.outcome_keys.insert(*key, *outcome).is_some() {
+                    return Err(anyhow!("Strategy key {} is used by two outcomes", key.0));
+                }
+            }
+            GameEntry::Move(..) => (),
+        }
+    }
+
+    if game.moves.is_empty() {
+        return Err(anyhow!("A game needs at least one move"));
+    }
+    if let Some((first, second)) =
+        game.all_moves()
+            .into_iter()
+            .tuple_combinations()
+            .find(|(first, second)| {
+                !game.beats.contains(&(*first, *second)) && !game.beats.contains(&(*second, *first))
+            })
+    {
+        return Err(anyhow!(
+            "Neither {} nor {} beats the other",
+            game.move_name(&first),
+            game.move_name(&second)
+        ));
+    }
+    if let Some(outcome) = [Outcome::Lose, Outcome::Draw, Outcome::Win]
+        .iter()
+        .find(|outcome| !game.outcome_scores.contains_key(outcome))
+    {
+        return Err(anyhow!("Outcome {:?} has no score", outcome));
+    }
+
+    Ok(game)
 }
 
-fn parse_strategy() -> impl Parser<char, StrategyKey, Error = Simple<char>> {
-    let lose = just('X').to(StrategyKey::X);
-    let draw = just('Y').to(StrategyKey::Y);
-    let win = just('Z').to(StrategyKey::Z);
+fn score<T>(game: &Game, input: &ParseOutput, mapping: &HashMap<StrategyKey, T>) -> Result<usize>
+where
+    T: Copy,
+    (Move, T): Round,
+{
+    input
+        .iter()
+        .map(|(symbol, key)| {
+            let opponents_move = game.opponent_move(*symbol)?;
+            let strategy = mapping
+                .get(key)
+                .ok_or_else(|| anyhow!("Strategy key {} has no mapping", key.0))?;
+            (opponents_move, *strategy).get_round_result(game)
+        })
+        .sum()
+}
 
-    lose.or(draw).or(win)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MappingScore {
+    total: usize,
+    mapping: String,
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    let score = input
+fn search_mappings<T>(
+    game: &Game,
+    input: &ParseOutput,
+    mapping: &HashMap<StrategyKey, T>,
+    values: Vec<T>,
+    name: impl Fn(&T) -> String,
+) -> Result<(MappingScore, MappingScore)>
+where
+    T: Copy,
+    (Move, T): Round,
+{
+    let keys = mapping
+        .keys()
+        .copied()
+        .sorted_by_key(|key| key.0)
+        .collect::<Vec<_>>();
+    let rounds = input.iter().counts();
+
+    let scores = values
         .into_iter()
-        .map(|(opponents_move, strategy_key)| match arguments.strategy {
-            Strategy::Roshambo => (opponents_move, strategy_key.to_roshambo()).get_round_result(),
-            Strategy::Outcome => (opponents_move, strategy_key.to_outcome()).get_round_result(),
+        .permutations(keys.len())
+        .map(|permutation| {
+            let mapping = keys
+                .iter()
+                .copied()
+                .zip(permutation)
+                .collect::<HashMap<_, _>>();
+            let total = rounds
+                .iter()
+                .map(|((symbol, key), count)| {
+                    let strategy = mapping
+                        .get(key)
+                        .ok_or_else(|| anyhow!("Strategy key {} has no mapping", key.0))?;
+                    (game.opponent_move(*symbol)?, *strategy)
+                        .get_round_result(game)
+                        .map(|result| result * count)
+                })
+                .sum::<Result<usize>>()?;
+            let mapping = keys
+                .iter()
+                .map(|key| format!("{}={}", key.0, name(&mapping[key])))
+                .join(",");
+            Ok(MappingScore { total, mapping })
         })
-        .sum();
+        .collect::<Result<Vec<_>>>()?;
+
+    let best = scores
+        .iter()
+        .min_by_key(|score| Reverse(score.total))
+        .ok_or_else(|| anyhow!("There are no strategy keys to map"))?;
+    let worst = scores
+        .iter()
+        .min_by_key(|score| score.total)
+        .ok_or_else(|| anyhow!("There are no strategy keys to map"))?;
+
+    Ok((best.clone(), worst.clone()))
+}
+
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<CommandResult> {
+    let game = match &arguments.game {
+        Some(path) => parse_game_file(file_to_string(path)?)?,
+        None => parse_game_file(ROCK_PAPER_SCISSORS.to_string())?,
+    };
+
+    if arguments.search {
+        let (best, worst) = match arguments.strategy {
+            Strategy::Roshambo => search_mappings(
+                &game,
+                &input,
+                &game.move_keys,
+                game.all_moves(),
+                |game_move| game.move_name(game_move).to_string(),
+            )?,
+            Strategy::Outcome => search_mappings(
+                &game,
+                &input,
+                &game.outcome_keys,
+                vec![Outcome::Lose, Outcome::Draw, Outcome::Win],
+                |outcome| outcome.name().to_string(),
+            )?,
+        };
+        return Ok(format!(
+            "Best total of {} with {}\nWorst total of {} with {}",
+            best.total, best.mapping, worst.total, worst.mapping
+        )
+        .into());
+    }
 
-    score
+    match arguments.strategy {
+        Strategy::Roshambo => score(&game, &input, &game.move_mapping(&arguments.mapping)?),
+        Strategy::Outcome => score(&game, &input, &game.outcome_mapping(&arguments.mapping)?),
+    }
+    .map(|total| total.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../day02/sample.txt");
+    const ROCK_PAPER_SCISSORS_LIZARD_SPOCK: &str =
+        include_str!("../day02/rock_paper_scissors_lizard_spock.txt");
+
+    fn arguments(strategy: Strategy, mapping: &[&str]) -> CommandLineArguments {
+        CommandLineArguments {
+            strategy,
+            game: None,
+            mapping: mapping.iter().map(|entry| entry.to_string()).collect(),
+            search: false,
+        }
+    }
+
+    #[test]
+    fn parse_round_reads_symbols() {
+        assert_eq!(parse_round().parse("A X"), Ok(('A', StrategyKey('X'))));
+        assert_eq!(parse_round().parse("E V"), Ok(('E', StrategyKey('V'))));
+        assert!(parse_round().then_ignore(end()).parse("AX").is_err());
+    }
+
+    #[test]
+    fn rock_paper_scissors_scores_the_sample() {
+        let input = parse_file(SAMPLE.to_string()).unwrap();
+        let total = |arguments| run(input.clone(), arguments).unwrap().to_string();
+
+        assert_eq!(total(arguments(Strategy::Roshambo, &[])), "15");
+        assert_eq!(total(arguments(Strategy::Outcome, &[])), "12");
+        assert_eq!(
+            total(arguments(Strategy::Roshambo, &["X=Scissors", "Z=Rock"])),
+            ((2 + 6) + (3 + 6) + (1 + 6)).to_string()
+        );
+    }
+
+    #[test]
+    fn search_finds_the_best_and_worst_mappings() {
+        let game = parse_game_file(ROCK_PAPER_SCISSORS.to_string()).unwrap();
+        let input = parse_file(SAMPLE.to_string()).unwrap();
+        let name = |game_move: &Move| game.move_name(game_move).to_string();
+
+        let (best, worst) =
+            search_mappings(&game, &input, &game.move_keys, game.all_moves(), name).unwrap();
+
+        assert_eq!(
+            best,
+            MappingScore {
+                total: 24,
+                mapping: "X=Scissors,Y=Paper,Z=Rock".to_string()
+            }
+        );
+        assert_eq!(worst.total, 6);
+        let mapping = worst.mapping.split(',').map(|entry| entry.to_string());
+        assert_eq!(
+            score(
+                &game,
+                &input,
+                &game.move_mapping(&mapping.collect_vec()).unwrap()
+            )
+            .unwrap(),
+            6
+        );
+    }
+
+    #[test]
+    fn games_are_driven_by_their_definition() {
+        let game = parse_game_file(ROCK_PAPER_SCISSORS_LIZARD_SPOCK.to_string()).unwrap();
+        let spock = game.find_move("spock").unwrap();
+        let lizard = game.find_move("Lizard").unwrap();
+        let rock = game.find_move("Rock").unwrap();
+
+        assert_eq!(game.get_outcome(lizard, spock), Outcome::Win);
+        assert_eq!(game.get_outcome(rock, spock), Outcome::Lose);
+        assert_eq!(game.get_move(rock, &Outcome::Win), Some(spock));
+        assert_eq!((rock, Outcome::Win).get_round_result(&game).unwrap(), 5 + 6);
+        assert_eq!((rock, lizard).get_round_result(&game).unwrap(), 4);
+
+        let input = parse_file("E V\nD Z\nA Y".to_string()).unwrap();
+        assert_eq!(
+            score(&game, &input, &game.move_mapping(&[]).unwrap()).unwrap(),
+            1 + 5 + 4
+        );
+    }
+
+    #[test]
+    fn tied_moves_prefer_the_first_defined() {
+        let game = parse_game_file(
+            "move Rock 1 A X\nmove Cloth 2 B Y\nmove Paper 2 C Z\nbeats Cloth Rock\nbeats Paper Rock\nbeats Paper Cloth\noutcome lose 0 X\noutcome draw 3 Y\noutcome win 6 Z"
+                .to_string(),
+        )
+        .unwrap();
+        let rock = game.find_move("Rock").unwrap();
+
+        assert_eq!(game.get_move(rock, &Outcome::Win), game.find_move("Cloth"));
+    }
+
+    #[test]
+    fn game_definitions_are_validated() {
+        let error = |definition: &str| {
+            parse_game_file(definition.to_string())
+                .unwrap_err()
+                .to_string()
+        };
+        let outcomes = "outcome lose 0 X\noutcome draw 3 Y\noutcome win 6 Z";
+
+        assert_eq!(
+            error(&format!("move Rock 1 A X\nbeats Rock Paper\n{}", outcomes)),
+            "Paper is not a move in this game"
+        );
+        assert_eq!(
+            error(&format!(
+                "move Rock 1 A X\nmove Paper 2 B Y\nbeats Rock Paper\nbeats Paper Rock\n{}",
+                outcomes
+            )),
+            "Paper and Rock cannot beat each other"
+        );
+        assert_eq!(
+            error("move Rock 1 A X\noutcome lose 0 X\noutcome win 6 Z"),
+            "Outcome Draw has no score"
+        );
+        assert_eq!(
+            error(&format!("move Rock 1 A X\nmove Paper 2 A Y\n{}", outcomes)),
+            "Opponent symbol A is used twice"
+        );
+        assert_eq!(
+            error(&format!(
+                "move Rock 1 A X\nmove Paper 2 B Y\nmove Scissors 3 C Z\nbeats Paper Rock\nbeats Rock Scissors\n{}",
+                outcomes
+            )),
+            "Neither Paper nor Scissors beats the other"
+        );
+        assert_eq!(
+            error(&format!(
+                "move Rock 1 A X\nmove Paper 2 B Y\nbeats Paper Rock\nbeats Paper Rock\n{}",
+                outcomes
+            )),
+            "Paper beats Rock twice"
+        );
+    }
 }
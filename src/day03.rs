@@ -1,50 +1,84 @@
-use std::{cell::LazyCell, collections::BTreeSet};
+use std::cell::LazyCell;
 
-use adventofcode2022::{flag_arg, parse_lines, single_arg, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{parse_lines, Command, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, one_of},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use itertools::Itertools;
 
 type ParseOutput = Vec<Vec<char>>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ItemSet(u64);
+
+impl ItemSet {
+    const ALL: ItemSet = ItemSet((1 << 52) - 1);
+
+    fn from_items(items: &[char]) -> Result<ItemSet> {
+        items
+            .iter()
+            .map(|item| item_priority(*item))
+            .try_fold(ItemSet(0), |set, priority| {
+                Ok(ItemSet(set.0 | 1 << (priority? - 1)))
+            })
+    }
+
+    fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    fn priority(&self) -> usize {
+        (0..52)
+            .filter(|bit| self.0 & (1 << bit) != 0)
+            .map(|bit| bit + 1)
+            .sum()
+    }
+}
+
+fn item_priority(item: char) -> Result<usize> {
+    match item {
+        'a'..='z' => Ok(item as usize - 'a' as usize + 1),
+        'A'..='Z' => Ok(item as usize - 'A' as usize + 27),
+        _ => Err(anyhow!("{:?} is not an item, items are a-z or A-Z", item)),
+    }
+}
+
 pub const DAY_03: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let split = flag_arg("split", 's', "Splits each rucksack in half");
-    let group_size = single_arg(
-        "group",
-        'g',
-        "How many rucks sacks should be included to check for common items.",
-    )
-    .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day03",
         "Finds common items in elfen rucksacks and find their score.",
         "Path to the input file. File should contain one rucksack in each line. Rucksacks are represented by acii letters and are case sensitive.",
-    vec![split, group_size], parse_arguments, parse_file, run)
-        .with_part1(CommandLineArguments { split_sack: true, group_size: 1 }, "Split each rucksack in half and find the common item. Sum the common item's score.")
-        .with_part2(CommandLineArguments { split_sack: false, group_size: 3 }, "Find the common item in every 3 rucksacks. Sum the common item's score.");
+    parse_file, run)
+        .with_part1(CommandLineArguments { split_sack: true, compartments: None, group_size: 1 }, "Split each rucksack in half and find the common item. Sum the common item's score.")
+        .with_part2(CommandLineArguments { split_sack: false, compartments: None, group_size: 3 }, "Find the common item in every 3 rucksacks. Sum the common item's score.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long = "split", help = "Splits each rucksack in half")]
     split_sack: bool,
+    #[arg(
+        short,
+        long,
+        value_name = "K",
+        conflicts_with = "split_sack",
+        help = "Splits each rucksack into K equal compartments"
+    )]
+    compartments: Option<usize>,
+    #[arg(
+        short,
+        long = "group",
+        value_name = "GROUP",
+        help = "How many rucks sacks should be included to check for common items."
+    )]
     group_size: usize,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    let split_sack = *args.get_one::<bool>("split").expect("Required arg");
-    let group_size = *args.get_one::<usize>("group").expect("Required arg");
-    CommandLineArguments {
-        split_sack,
-        group_size,
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -58,40 +92,84 @@ fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
     parse_lines(one_of(upper).repeated()).then_ignore(end())
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    input
-        .into_iter()
-        .map(|sack| {
-            if arguments.split_sack {
-                let (left, right) = sack.split_at(sack.len() / 2);
-                let left_set: BTreeSet<char> = left.into_iter().cloned().collect();
-                let right_set: BTreeSet<char> = right.into_iter().cloned().collect();
-                left_set
-                    .intersection(&right_set)
-                    .into_iter()
-                    .cloned()
-                    .collect()
-            } else {
-                sack
-            }
+impl CommandLineArguments {
+    fn compartments(&self) -> usize {
+        match (self.compartments, self.split_sack) {
+            (Some(compartments), _) => compartments,
+            (None, true) => 2,
+            (None, false) => 1,
+        }
+    }
+}
+
+fn common_items(sack: &[char], compartments: usize) -> Result<ItemSet> {
+    if compartments == 0 || !sack.len().is_multiple_of(compartments) {
+        return Err(anyhow!(
+            "A rucksack with {} items cannot be split into {} equal compartments",
+            sack.len(),
+            compartments
+        ));
+    }
+    if sack.is_empty() {
+        return Ok(ItemSet(0));
+    }
+
+    sack.chunks(sack.len() / compartments)
+        .map(ItemSet::from_items)
+        .try_fold(ItemSet::ALL, |common, compartment| {
+            Ok(common.intersection(&compartment?))
         })
+}
+
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    if arguments.group_size == 0 {
+        return Err(anyhow!("Groups need at least one rucksack"));
+    }
+    let compartments = arguments.compartments();
+
+    input
+        .iter()
+        .map(|sack| common_items(sack, compartments))
         .chunks(arguments.group_size)
         .into_iter()
         .map(|group| {
             group
-                .map(|sack| sack.into_iter().collect::<BTreeSet<char>>())
-                .reduce(|acc, sack| acc.intersection(&sack).into_iter().cloned().collect())
+                .reduce(|common, sack| Ok(common?.intersection(&sack?)))
                 .expect("At least one sack")
-                .into_iter()
-                .map(|c| {
-                    if c.is_uppercase() {
-                        (c as usize - 'A' as usize) + 27
-                    } else {
-                        (c as usize - 'a' as usize) + 1
-                    }
-                })
-                .into_iter()
-                .sum::<usize>()
+                .map(|common| common.priority())
         })
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn item_sets_intersect_and_score() {
+        let left = ItemSet::from_items(&"vJrwpWtwJgWr".chars().collect_vec()).unwrap();
+        let right = ItemSet::from_items(&"hcsFMMfFFhFp".chars().collect_vec()).unwrap();
+
+        assert_eq!(left.intersection(&right).priority(), 16);
+        assert_eq!(ItemSet::ALL.priority(), (1..=52).sum());
+        assert!(ItemSet::from_items(&['a', '1']).is_err());
+    }
+
+    #[test]
+    fn sacks_split_into_equal_compartments() {
+        let sack = "abcXaYbZcQ".chars().collect_vec();
+
+        assert_eq!(common_items(&sack, 5).unwrap().priority(), 0);
+        assert_eq!(
+            common_items(&sack[..6], 2).unwrap(),
+            ItemSet::from_items(&['a']).unwrap()
+        );
+        assert!(common_items(&sack, 3).is_err());
+        assert!(common_items(&sack, 0).is_err());
+    }
+
+    #[test]
+    fn parser_rejects_non_letters() {
+        assert!(parse_file("abc1".to_string()).is_err());
+    }
+}
@@ -1,49 +1,87 @@
-use adventofcode2022::{parse_lines, parse_usize, single_arg, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{
+    parse_lines, parse_usize, Command, CommandResult, Interval, IntervalRelation, IntervalSet,
+    ParseError, Problem,
+};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::{ArgMatches, ValueEnum};
-use std::cell::LazyCell;
+use clap::{builder::PossibleValue, Args, ValueEnum};
+use itertools::Itertools;
+use std::{cell::LazyCell, sync::LazyLock};
 
-type ParseOutput = Vec<((usize, usize), (usize, usize))>;
+type ParseOutput = Vec<(Interval<usize>, Interval<usize>)>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    overlap: OverlapCountStrategy,
+    #[arg(
+        short,
+        long,
+        required_unless_present = "report",
+        help = "The overlap count strategy. Use Full to count only full overlapping work, Any for partial overlapping, or an interval relation to count pairs where the first elf's work has that relation to the second's"
+    )]
+    overlap: Option<OverlapCountStrategy>,
+    #[arg(
+        short,
+        long,
+        conflicts_with = "overlap",
+        help = "Reports how many pairs have each interval relation, and the sections covered and left uncovered by every elf"
+    )]
+    report: bool,
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Clone)]
 pub enum OverlapCountStrategy {
     Full,
     Any,
+    Relation(IntervalRelation),
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        overlap: args
-            .get_one::<OverlapCountStrategy>("overlap")
-            .expect("Valid arguments")
-            .clone(),
+impl ValueEnum for OverlapCountStrategy {
+    fn value_variants<'a>() -> &'a [Self] {
+        static VARIANTS: LazyLock<Vec<OverlapCountStrategy>> = LazyLock::new(|| {
+            [OverlapCountStrategy::Full, OverlapCountStrategy::Any]
+                .into_iter()
+                .chain(IntervalRelation::ALL.map(OverlapCountStrategy::Relation))
+                .collect()
+        });
+        &VARIANTS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            OverlapCountStrategy::Full => Some(PossibleValue::new("full")),
+            OverlapCountStrategy::Any => Some(PossibleValue::new("any")),
+            OverlapCountStrategy::Relation(relation) => relation.to_possible_value(),
+        }
+    }
+}
+
+impl OverlapCountStrategy {
+    fn matches(&self, first: &Interval<usize>, second: &Interval<usize>) -> bool {
+        let first_set = IntervalSet::from(*first);
+        let second_set = IntervalSet::from(*second);
+        let overlap = first_set.intersect(&second_set);
+        match self {
+            OverlapCountStrategy::Full => overlap == first_set || overlap == second_set,
+            OverlapCountStrategy::Any => !overlap.is_empty(),
+            OverlapCountStrategy::Relation(relation) => first.relation(second) == Some(*relation),
+        }
     }
 }
 
 pub const DAY_04: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let overlap = single_arg("overlap", 'o', "The overlap count strategy. Use Full to count only full overlapping work, or Any for partial overlapping")
-        .value_parser(clap::value_parser!(OverlapCountStrategy));
     let problem = Problem::new(
         "day04",
         "Counts the number of elf paris which have overlapping work.",
         "Path to the input file. Each line is a comma serperated pair of work sections. Each work section start and end section is seperated by a -",
-        vec![overlap],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { overlap: OverlapCountStrategy::Full }, "Counts the number of elf pairs where one is fully overlapping")
-    .with_part2(CommandLineArguments { overlap: OverlapCountStrategy::Any }, "Counts the number of elf pairs with any overlapping");
+    .with_part1(CommandLineArguments { overlap: Some(OverlapCountStrategy::Full), report: false }, "Counts the number of elf pairs where one is fully overlapping")
+    .with_part2(CommandLineArguments { overlap: Some(OverlapCountStrategy::Any), report: false }, "Counts the number of elf pairs with any overlapping");
     Box::new(problem)
 });
 
@@ -57,29 +95,261 @@ fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
     parse_lines(parse_group()).then_ignore(end())
 }
 
-fn parse_group() -> impl Parser<char, ((usize, usize), (usize, usize)), Error = Simple<char>> {
+fn parse_group() -> impl Parser<char, (Interval<usize>, Interval<usize>), Error = Simple<char>> {
     parse_pair().then_ignore(just(',')).then(parse_pair())
 }
 
-fn parse_pair() -> impl Parser<char, (usize, usize), Error = Simple<char>> {
-    parse_usize().then_ignore(just('-')).then(parse_usize())
+fn parse_pair() -> impl Parser<char, Interval<usize>, Error = Simple<char>> {
+    parse_usize()
+        .then_ignore(just('-'))
+        .then(parse_usize())
+        .map(|(start, end)| Interval::new(start, end))
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    input
-        .into_iter()
-        .filter(
-            |((first_start, first_end), (second_start, second_end))| match arguments.overlap {
-                OverlapCountStrategy::Full => {
-                    (first_start <= second_start && first_end >= second_end)
-                        || (second_start <= first_start && second_end >= first_end)
-                }
-                OverlapCountStrategy::Any => {
-                    (first_start < second_start && second_start <= first_end)
-                        || (second_start < first_start && first_start <= second_end)
-                        || first_start == second_start
+fn format_interval(interval: &Interval<usize>) -> String {
+    if interval.start == interval.end {
+        interval.start.to_string()
+    } else {
+        format!("{}-{}", interval.start, interval.end)
+    }
+}
+
+fn report(input: &ParseOutput) -> Result<String> {
+    let relations = input
+        .iter()
+        .map(|(first, second)| first.relation(second))
+        .counts();
+    let relation_lines = IntervalRelation::ALL
+        .iter()
+        .map(|relation| {
+            format!(
+                "{:?}: {}",
+                relation,
+                relations.get(&Some(*relation)).unwrap_or(&0)
+            )
+        })
+        .chain(
+            relations
+                .get(&None)
+                .map(|empty| format!("Empty assignment: {}", empty)),
+        );
+
+    let coverage = input
+        .iter()
+        .flat_map(|(first, second)| [*first, *second])
+        .collect::<IntervalSet<usize>>();
+    let span = coverage
+        .iter()
+        .next()
+        .zip(coverage.iter().last())
+        .map(|(first, last)| Interval::new(first.start, last.end));
+    let gaps = coverage.gaps().collect::<Vec<_>>();
+    let too_many = || anyhow!("The assignments cover too many sections to count");
+    let coverage_lines = [
+        match span {
+            Some(span) => format!(
+                "Covered {} of the {} sections from {}",
+                coverage.length().ok_or_else(too_many)?,
+                span.length().ok_or_else(too_many)?,
+                format_interval(&span)
+            ),
+            None => "Covered no sections".to_string(),
+        },
+        format!(
+            "Uncovered {} sections: {}",
+            gaps.iter()
+                .try_fold(0usize, |total, gap| total.checked_add(gap.length()?))
+                .ok_or_else(too_many)?,
+            if gaps.is_empty() {
+                "none".to_string()
+            } else {
+                gaps.iter().map(format_interval).join(", ")
+            }
+        ),
+    ];
+
+    Ok(relation_lines.chain(coverage_lines).join("\n"))
+}
+
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<CommandResult> {
+    match (arguments.report, arguments.overlap) {
+        (true, None) => report(&input).map(|report| report.into()),
+        (false, Some(overlap)) => Ok(input
+            .iter()
+            .filter(|(first, second)| overlap.matches(first, second))
+            .count()
+            .into()),
+        _ => Err(anyhow!(
+            "Either an overlap strategy or a report is required"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{property, Rng};
+    use std::{collections::HashSet, iter::once};
+
+    const SAMPLE: &str = include_str!("../day04/sample.txt");
+
+    fn generate_interval(rng: &mut Rng, size: usize) -> Interval<usize> {
+        let start = rng.usize_in(1..=size);
+        Interval::new(start, rng.usize_in(start..=size))
+    }
+
+    fn generate_input(rng: &mut Rng, size: usize) -> ParseOutput {
+        (0..rng.usize_in(1..=size))
+            .map(|_| (generate_interval(rng, size), generate_interval(rng, size)))
+            .collect()
+    }
+
+    fn reference(input: &ParseOutput, overlap: &OverlapCountStrategy) -> usize {
+        input
+            .iter()
+            .filter(|(first, second)| {
+                let first = (first.start..=first.end).collect::<HashSet<_>>();
+                let second = (second.start..=second.end).collect::<HashSet<_>>();
+                match overlap {
+                    OverlapCountStrategy::Full => {
+                        first.is_subset(&second) || second.is_subset(&first)
+                    }
+                    OverlapCountStrategy::Any => !first.is_disjoint(&second),
+                    _ => unreachable!("Only set based strategies have a reference"),
                 }
-            },
-        )
-        .count()
+            })
+            .count()
+    }
+
+    #[test]
+    fn overlap_arguments_accept_relations_and_conflict_with_report() {
+        let matches = |args: &[&str]| {
+            CommandLineArguments::augment_args(clap::Command::new("day04"))
+                .try_get_matches_from(once(&"day04").chain(args))
+        };
+
+        assert!(matches(&["-r", "-o", "any"]).is_err());
+        assert!(matches(&[]).is_err());
+        assert!(matches!(
+            OverlapCountStrategy::from_str("finished-by", true),
+            Ok(OverlapCountStrategy::Relation(IntervalRelation::FinishedBy))
+        ));
+        assert!(matches(&["-o", "met-by"]).is_ok());
+    }
+
+    #[test]
+    fn parse_pair_reads_inclusive_range() {
+        assert_eq!(parse_pair().parse("2-4"), Ok(Interval::new(2, 4)));
+        assert!(parse_pair().then_ignore(end()).parse("2-").is_err());
+    }
+
+    #[test]
+    fn parse_group_reads_both_elves() {
+        assert_eq!(
+            parse_group().parse("2-4,6-8"),
+            Ok((Interval::new(2, 4), Interval::new(6, 8)))
+        );
+    }
+
+    #[test]
+    fn run_matches_reference() {
+        property(100, 30, |rng, size| {
+            let input = generate_input(rng, size);
+            [OverlapCountStrategy::Full, OverlapCountStrategy::Any]
+                .into_iter()
+                .for_each(|overlap| {
+                    assert_eq!(
+                        run(
+                            input.clone(),
+                            CommandLineArguments {
+                                overlap: Some(overlap.clone()),
+                                report: false,
+                            }
+                        )
+                        .unwrap()
+                        .to_string(),
+                        reference(&input, &overlap).to_string()
+                    );
+                });
+        });
+    }
+
+    #[test]
+    fn relations_classify_every_pair() {
+        property(100, 30, |rng, size| {
+            let first = generate_interval(rng, size);
+            let second = generate_interval(rng, size);
+            let relation = first.relation(&second).expect("Intervals are not empty");
+
+            assert_eq!(second.relation(&first), Some(relation.inverse()));
+            assert_eq!(
+                relation.shares_values(),
+                first.intersection(&second).is_some()
+            );
+            assert_eq!(
+                OverlapCountStrategy::Full.matches(&first, &second),
+                matches!(
+                    relation,
+                    IntervalRelation::Starts
+                        | IntervalRelation::During
+                        | IntervalRelation::Finishes
+                        | IntervalRelation::Equals
+                        | IntervalRelation::FinishedBy
+                        | IntervalRelation::Contains
+                        | IntervalRelation::StartedBy
+                )
+            );
+        });
+
+        let relation = |first: (usize, usize), second: (usize, usize)| {
+            Interval::new(first.0, first.1).relation(&Interval::new(second.0, second.1))
+        };
+        assert_eq!(relation((2, 4), (6, 8)), Some(IntervalRelation::Before));
+        assert_eq!(relation((2, 4), (5, 8)), Some(IntervalRelation::Meets));
+        assert_eq!(relation((5, 7), (7, 9)), Some(IntervalRelation::Overlaps));
+        assert_eq!(relation((6, 6), (4, 6)), Some(IntervalRelation::Finishes));
+        assert_eq!(relation((2, 8), (3, 7)), Some(IntervalRelation::Contains));
+        assert_eq!(relation((4, 3), (3, 7)), None);
+    }
+
+    #[test]
+    fn report_counts_relations_and_coverage() {
+        let groups = parse_file(SAMPLE.to_string()).expect("Sample parses");
+        let sample = report(&groups).unwrap();
+
+        assert!(sample.contains("Before: 1"));
+        assert!(sample.contains("Meets: 1"));
+        assert!(sample.contains("Overlaps: 2"));
+        assert!(sample.contains("Contains: 1"));
+        assert!(sample.contains("Covered 8 of the 8 sections from 2-9"));
+        assert!(sample.contains("Uncovered 0 sections: none"));
+
+        let gaps = report(&vec![(Interval::new(1, 2), Interval::new(5, 5))]).unwrap();
+        assert!(gaps.ends_with("Covered 3 of the 5 sections from 1-5\nUncovered 2 sections: 3-4"));
+    }
+
+    #[test]
+    fn report_of_too_many_sections_is_an_error() {
+        let input = parse_file("0-18446744073709551615,2-3".to_string()).unwrap();
+        let arguments = CommandLineArguments {
+            overlap: None,
+            report: true,
+        };
+
+        assert_eq!(
+            run(input, arguments).err().map(|error| error.to_string()),
+            Some("The assignments cover too many sections to count".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_strategy_is_an_error() {
+        let input = parse_file(SAMPLE.to_string()).unwrap();
+        let arguments = CommandLineArguments {
+            overlap: None,
+            report: false,
+        };
+
+        assert!(run(input, arguments).is_err());
+    }
 }
@@ -1,46 +1,86 @@
-use adventofcode2022::{flag_arg, parse_lines, parse_usize, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{parse_lines, parse_usize, Command, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just, one_of},
     Parser,
 };
-use clap::ArgMatches;
-use std::cell::LazyCell;
+use clap::Args;
+use itertools::Itertools;
+use std::{cell::LazyCell, cmp::min, num::NonZeroUsize};
 
-type ParseOutput = (Vec<Vec<Option<char>>>, Vec<(usize, usize, usize)>);
+type ParseOutput = (Vec<Vec<char>>, Vec<(usize, usize, usize)>);
 
 pub const DAY_05: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let upgraded_crane = flag_arg(
-        "upgraded_crane",
-        'u',
-        "If the crane can move more than 1 box at a time",
-    );
-
     let problem = Problem::new(
         "day05",
         "Takes the current stacks as well as crane instructions for how to move boxes between stacks. Then returns the top of each stack after all moves",
         "Path to the input file. File should consist of the stacks, followed by a blank line, then newline delimited move instructions.",
-        vec![upgraded_crane],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { upgraded_crane: false }, "The crane moves each box one at a time.")
-    .with_part2(CommandLineArguments { upgraded_crane: true }, "part 2 help");
+    .with_part1(CommandLineArguments { crane: CraneModelArgs { upgraded_crane: false, capacity: None }, trace: false, emit: false }, "The crane moves each box one at a time.")
+    .with_part2(CommandLineArguments { crane: CraneModelArgs { upgraded_crane: true, capacity: None }, trace: false, emit: false }, "The crane moves every box of an instruction at once.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CraneModel {
+    CrateMover9000,
+    CrateMover9001,
+    Capacity(NonZeroUsize),
+}
+
+impl CraneModel {
+    fn lift_size(&self, count: usize) -> usize {
+        match self {
+            CraneModel::CrateMover9000 => 1,
+            CraneModel::CrateMover9001 => count,
+            CraneModel::Capacity(capacity) => capacity.get(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[command(flatten)]
+    crane: CraneModelArgs,
+    #[arg(
+        long,
+        help = "Prints the stacks after every instruction in the same format as the input"
+    )]
+    trace: bool,
+    #[arg(
+        long,
+        help = "Returns the final stacks as a new input diagram instead of the top crates"
+    )]
+    emit: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+#[group(multiple = false)]
+pub struct CraneModelArgs {
+    #[arg(
+        short,
+        long = "upgraded_crane",
+        help = "Uses the CrateMover 9001 which moves every crate of an instruction at once"
+    )]
     upgraded_crane: bool,
+    #[arg(
+        short,
+        long,
+        help = "Uses a crane that lifts at most this many crates at once"
+    )]
+    capacity: Option<NonZeroUsize>,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        upgraded_crane: *args
-            .get_one::<bool>("upgraded_crane")
-            .expect("Valid arguments"),
+impl From<CraneModelArgs> for CraneModel {
+    fn from(args: CraneModelArgs) -> Self {
+        match (args.upgraded_crane, args.capacity) {
+            (_, Some(capacity)) => CraneModel::Capacity(capacity),
+            (true, None) => CraneModel::CrateMover9001,
+            (false, None) => CraneModel::CrateMover9000,
+        }
     }
 }
 
@@ -51,12 +91,26 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_crate_line())
-        .then_ignore(parse_crate_line_numbers())
+    parse_stacks()
         .then(parse_lines(parse_instruction()))
         .then_ignore(end())
 }
 
+fn parse_stacks() -> impl Parser<char, Vec<Vec<char>>, Error = Simple<char>> {
+    parse_lines(parse_crate_line())
+        .then(parse_crate_line_numbers())
+        .try_map(|(crates, numbers), span| {
+            let width = crates.iter().map(|row| row.len()).max().unwrap_or(0);
+            if width > numbers.len() || !numbers.iter().copied().eq(1..=numbers.len()) {
+                return Err(Simple::custom(
+                    span,
+                    "Stacks should be numbered from 1 below every column of crates",
+                ));
+            }
+            Ok(convert_to_stacks(crates, numbers.len()))
+        })
+}
+
 fn parse_instruction() -> impl Parser<char, (usize, usize, usize), Error = Simple<char>> {
     just("move ")
         .ignore_then(parse_usize())
@@ -89,35 +143,102 @@ fn parse_crate() -> impl Parser<char, char, Error = Simple<char>> {
         .then_ignore(just(']'))
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> String {
-    let mut stacks = convert_to_stacks(input.0);
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<String> {
+    let mut stacks = input.0;
+    let crane = CraneModel::from(arguments.crane);
 
-    input
-        .1
-        .into_iter()
-        .map(|(m, f, t)| (m, f - 1, t - 1))
-        .for_each(|(m, f, t)| {
-            let from = stacks.get_mut(f).expect("stack exists");
-            let mut popped = (0..m)
-                .map(|_| from.pop().expect("Non empty stack"))
-                .collect::<Vec<char>>();
-
-            if arguments.upgraded_crane {
-                popped.reverse();
-            }
+    if arguments.trace {
+        println!("Initial stacks");
+        println!("{}", format_stacks(&stacks));
+        println!();
+    }
 
-            let to = stacks.get_mut(t).expect("stack exists");
-            to.append(&mut popped);
-        });
+    input.1.into_iter().try_for_each(|instruction| {
+        move_crates(&mut stacks, instruction, &crane)?;
+        if arguments.trace {
+            println!("{}", format_instruction(&instruction));
+            println!("{}", format_stacks(&stacks));
+            println!();
+        }
+        Ok::<(), anyhow::Error>(())
+    })?;
 
-    stacks
+    if arguments.emit {
+        return Ok(format_input(&(stacks, Vec::new())));
+    }
+
+    Ok(stacks
         .into_iter()
         .filter_map(|stack| stack.last().cloned())
-        .collect()
+        .collect())
 }
 
-fn convert_to_stacks(crates: Vec<Vec<Option<char>>>) -> Vec<Vec<char>> {
-    let mut stacks = vec![Vec::new(); crates.len()];
+fn move_crates(
+    stacks: &mut [Vec<char>],
+    (count, f, t): (usize, usize, usize),
+    crane: &CraneModel,
+) -> Result<()> {
+    let from = stack_index(f, stacks)?;
+    let to = stack_index(t, stacks)?;
+    let height = stacks[from].len();
+    if height < count {
+        return Err(anyhow!(
+            "Cannot move {} crates from stack {} which only has {}",
+            count,
+            f,
+            height
+        ));
+    }
+
+    let mut remaining = count;
+    while remaining > 0 {
+        let lift = min(remaining, crane.lift_size(count));
+        let height = stacks[from].len();
+        let mut lifted = stacks[from].split_off(height - lift);
+        stacks[to].append(&mut lifted);
+        remaining -= lift;
+    }
+
+    Ok(())
+}
+
+fn format_stacks(stacks: &[Vec<char>]) -> String {
+    let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+    let rows = (0..height).rev().map(|row| {
+        stacks
+            .iter()
+            .map(|stack| match stack.get(row) {
+                Some(c) => format!("[{}]", c),
+                None => "   ".to_string(),
+            })
+            .join(" ")
+    });
+    let numbers = format!(" {} ", (1..=stacks.len()).join("   "));
+
+    rows.chain([numbers]).join("\n")
+}
+
+fn format_instruction((count, from, to): &(usize, usize, usize)) -> String {
+    format!("move {} from {} to {}", count, from, to)
+}
+
+fn format_input((stacks, instructions): &ParseOutput) -> String {
+    format!(
+        "{}\n\n{}",
+        format_stacks(stacks),
+        instructions.iter().map(format_instruction).join("\n")
+    )
+}
+
+fn stack_index(stack: usize, stacks: &[Vec<char>]) -> Result<usize> {
+    match stack {
+        s if s >= 1 && s <= stacks.len() => Ok(s - 1),
+        s => Err(anyhow!("Stack {} does not exist", s)),
+    }
+}
+
+fn convert_to_stacks(crates: Vec<Vec<Option<char>>>, stack_count: usize) -> Vec<Vec<char>> {
+    let mut stacks = vec![Vec::new(); stack_count];
 
     crates.into_iter().for_each(|row| {
         row.into_iter()
@@ -135,3 +256,136 @@ fn convert_to_stacks(crates: Vec<Vec<Option<char>>>) -> Vec<Vec<char>> {
 
     stacks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::FromArgMatches;
+    use std::iter::once;
+
+    const SAMPLE: &str = include_str!("../day05/sample.txt");
+
+    #[test]
+    fn parse_crate_line_reads_gaps() {
+        assert_eq!(
+            parse_crate_line().parse("    [D]    "),
+            Ok(vec![None, Some('D'), None])
+        );
+    }
+
+    #[test]
+    fn parse_instruction_reads_move() {
+        assert_eq!(
+            parse_instruction().parse("move 3 from 1 to 2"),
+            Ok((3, 1, 2))
+        );
+    }
+
+    #[test]
+    fn parse_stacks_keeps_empty_stacks() {
+        assert_eq!(
+            parse_stacks().parse("[A]        \n 1   2   3 \n\n"),
+            Ok(vec![vec!['A'], vec![], vec![]])
+        );
+        assert!(parse_stacks().parse("[A] [B]\n 1 \n\n").is_err());
+        assert!(parse_stacks().parse("[A]\n 2 \n\n").is_err());
+    }
+
+    fn stacks(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn crane_models_lift_different_amounts() {
+        let run_crane = |crane| {
+            let mut crates = stacks(&["ABCDE", ""]);
+            move_crates(&mut crates, (5, 1, 2), &crane).unwrap();
+            crates[1].iter().collect::<String>()
+        };
+
+        assert_eq!(run_crane(CraneModel::CrateMover9000), "EDCBA");
+        assert_eq!(run_crane(CraneModel::CrateMover9001), "ABCDE");
+        assert_eq!(
+            run_crane(CraneModel::Capacity(NonZeroUsize::new(2).unwrap())),
+            "DEBCA"
+        );
+        assert_eq!(run_crane(CraneModel::Capacity(NonZeroUsize::MIN)), "EDCBA");
+    }
+
+    #[test]
+    fn crane_arguments_pick_one_model() {
+        let crane = |args: &[&str]| {
+            let matches = CommandLineArguments::augment_args(clap::Command::new("day05"))
+                .try_get_matches_from(once(&"day05").chain(args))?;
+            CommandLineArguments::from_arg_matches(&matches)
+                .map(|args| CraneModel::from(args.crane))
+        };
+
+        assert_eq!(crane(&[]).unwrap(), CraneModel::CrateMover9000);
+        assert_eq!(crane(&["-u"]).unwrap(), CraneModel::CrateMover9001);
+        assert_eq!(
+            crane(&["-c", "3"]).unwrap(),
+            CraneModel::Capacity(NonZeroUsize::new(3).unwrap())
+        );
+        assert!(crane(&["-c", "0"]).is_err());
+        assert!(crane(&["-u", "-c", "3"]).is_err());
+    }
+
+    #[test]
+    fn moving_from_a_short_stack_is_an_error() {
+        let mut crates = stacks(&["A", "B"]);
+        let error = move_crates(&mut crates, (2, 1, 2), &CraneModel::CrateMover9000);
+
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "Cannot move 2 crates from stack 1 which only has 1"
+        );
+        assert!(move_crates(&mut crates, (1, 3, 1), &CraneModel::CrateMover9000).is_err());
+    }
+
+    #[test]
+    fn format_stacks_draws_the_input_diagram() {
+        let input = parse_file(SAMPLE.to_string()).expect("Sample parses");
+        let diagram = SAMPLE.split("\n\n").next().unwrap();
+
+        assert_eq!(format_stacks(&input.0), diagram);
+    }
+
+    #[test]
+    fn parser_round_trips_sample() {
+        let input = parse_file(SAMPLE.to_string()).expect("Sample parses");
+
+        assert_eq!(format_input(&input), SAMPLE.trim_end());
+    }
+
+    #[test]
+    fn emitted_final_state_is_a_valid_input() {
+        let input = parse_file(SAMPLE.to_string()).expect("Sample parses");
+        let arguments = CommandLineArguments {
+            crane: CraneModelArgs {
+                upgraded_crane: false,
+                capacity: None,
+            },
+            trace: false,
+            emit: true,
+        };
+        let emitted = run(input, arguments.clone()).unwrap();
+
+        assert_eq!(
+            emitted,
+            "        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3 \n\n"
+        );
+        let reparsed = parse_file(emitted).expect("Emitted state parses");
+        assert_eq!(
+            run(
+                reparsed,
+                CommandLineArguments {
+                    emit: false,
+                    ..arguments
+                }
+            )
+            .unwrap(),
+            "CMZ"
+        );
+    }
+}
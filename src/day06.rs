@@ -1,46 +1,106 @@
-use adventofcode2022::{single_arg, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{Command, CommandResult, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
-    primitive::{end, take_until},
-    text, Parser,
+    primitive::{end, filter, just},
+    Parser,
+};
+use clap::Args;
+use itertools::Itertools;
+use std::{
+    cell::LazyCell,
+    collections::{HashMap, VecDeque},
+    io::BufRead,
+    iter::once,
 };
-use clap::ArgMatches;
-use std::{cell::LazyCell, collections::BTreeSet};
 
 type ParseOutput = Vec<char>;
 
 pub const DAY_06: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let number = single_arg("number", 'n', "The number of unique characters to find")
-        .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day06",
         "day6 finds unique sets of strings in a message packet.",
         "Path to the input file. Input should be one line for the message",
-        vec![number],
-        parse_arguments,
         parse_file,
         run,
     )
+    .with_stream(run_stream)
     .with_part1(
-        CommandLineArguments { n: 4 },
+        CommandLineArguments {
+            n: vec![4],
+            all: false,
+        },
         "Finds the first set of 4 unique characters in the input string.",
     )
     .with_part2(
-        CommandLineArguments { n: 14 },
+        CommandLineArguments {
+            n: vec![14],
+            all: false,
+        },
         "Finds the first set of 14 unique characters in the input string.",
     );
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    n: usize,
+    #[arg(
+        short,
+        long = "number",
+        value_name = "NUMBER",
+        required = true,
+        value_delimiter = ',',
+        help = "The number of unique characters to find, comma delimited to find several markers in one pass"
+    )]
+    n: Vec<usize>,
+    #[arg(
+        short,
+        long,
+        help = "Finds every position a marker ends at instead of only the first"
+    )]
+    all: bool,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        n: *args.get_one::<usize>("number").expect("Valid arguments"),
+#[derive(Debug, Clone)]
+struct MarkerDetector {
+    size: usize,
+    window: VecDeque<char>,
+    counts: HashMap<char, usize>,
+    positions: Vec<usize>,
+}
+
+impl MarkerDetector {
+    fn new(size: usize) -> MarkerDetector {
+        MarkerDetector {
+            size,
+            window: VecDeque::new(),
+            counts: HashMap::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, position: usize, character: char) {
+        self.window.push_back(character);
+        *self.counts.entry(character).or_insert(0) += 1;
+
+        if self.window.len() > self.size {
+            if let Some(removed) = self.window.pop_front() {
+                if let Some(count) = self.counts.get_mut(&removed) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.counts.remove(&removed);
+                    }
+                }
+            }
+        }
+
+        if self.counts.len() == self.size {
+            self.positions.push(position);
+        }
+    }
+
+    fn found(&self) -> bool {
+        !self.positions.is_empty()
     }
 }
 
@@ -51,20 +111,237 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    take_until(text::newline())
-        .map(|(r, _)| r)
+    filter(char::is_ascii_lowercase)
+        .repeated()
+        .then_ignore(just('\r').or_not().then(just('\n')).or_not())
         .then_ignore(end())
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    input
-        .windows(arguments.n)
-        .into_iter()
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<CommandResult> {
+    find_markers(input.into_iter().map(Ok), &arguments)
+        .and_then(|detectors| summarize(detectors, &arguments))
+}
+
+fn run_stream(reader: Box<dyn BufRead>, arguments: CommandLineArguments) -> Result<CommandResult> {
+    find_markers(stream_chars(reader), &arguments)
+        .and_then(|detectors| summarize(detectors, &arguments))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamState {
+    Signal,
+    CarriageReturn,
+    Ended,
+}
+
+// Accepts exactly what `parse_file` does: one line of lowercase letters, optionally ended by a
+// newline, with nothing after it.
+fn stream_chars(reader: impl BufRead) -> impl Iterator<Item = Result<char>> {
+    reader
+        .bytes()
+        .map(Some)
+        .chain(once(None))
         .enumerate()
-        .find(|(_, chars)| {
-            chars.into_iter().cloned().collect::<BTreeSet<char>>().len() == arguments.n
+        .scan(StreamState::Signal, |state, (index, byte)| {
+            let position = index + 1;
+            Some(match (*state, byte) {
+                (_, Some(Err(error))) => Some(Err(error.into())),
+                (StreamState::CarriageReturn, None) => Some(Err(anyhow!(
+                    "The carriage return at the end is not a newline"
+                ))),
+                (_, None) => None,
+                (StreamState::Signal | StreamState::CarriageReturn, Some(Ok(b'\n'))) => {
+                    *state = StreamState::Ended;
+                    None
+                }
+                (StreamState::Signal, Some(Ok(b'\r'))) => {
+                    *state = StreamState::CarriageReturn;
+                    None
+                }
+                (StreamState::Signal, Some(Ok(byte))) if byte.is_ascii_lowercase() => {
+                    Some(Ok(char::from(byte)))
+                }
+                (StreamState::Ended, Some(Ok(_))) => Some(Err(anyhow!(
+                    "Byte {} comes after the end of the signal, which must be a single line",
+                    position
+                ))),
+                (_, Some(Ok(byte))) => Some(Err(anyhow!(
+                    "Byte {:#04x} at position {} is not a lowercase letter",
+                    byte,
+                    position
+                ))),
+            })
         })
-        .map(|(position, _)| position)
-        .expect("valid message")
-        + arguments.n
+        .flatten()
+}
+
+fn find_markers(
+    signal: impl Iterator<Item = Result<char>>,
+    arguments: &CommandLineArguments,
+) -> Result<Vec<MarkerDetector>> {
+    if arguments.n.contains(&0) {
+        return Err(anyhow!("A marker needs at least 1 character"));
+    }
+
+    let mut detectors = arguments
+        .n
+        .iter()
+        .map(|size| MarkerDetector::new(*size))
+        .collect::<Vec<_>>();
+
+    // Keeps reading once every marker is found so that the rest of the signal is still checked.
+    for (index, character) in signal.enumerate() {
+        let character = character?;
+        if arguments.all || !detectors.iter().all(MarkerDetector::found) {
+            detectors
+                .iter_mut()
+                .for_each(|detector| detector.push(index + 1, character));
+        }
+    }
+
+    Ok(detectors)
+}
+
+fn summarize(
+    detectors: Vec<MarkerDetector>,
+    arguments: &CommandLineArguments,
+) -> Result<CommandResult> {
+    match detectors.as_slice() {
+        [detector] if !arguments.all => detector
+            .positions
+            .first()
+            .map(|position| (*position).into())
+            .ok_or_else(|| anyhow!("No marker of {} distinct characters", detector.size)),
+        _ => Ok(detectors
+            .iter()
+            .map(
+                |detector| match (detector.positions.as_slice(), arguments.all) {
+                    ([], _) => format!("No marker of {} distinct characters", detector.size),
+                    ([first, ..], false) => format!(
+                        "Marker of {} distinct characters ends at {}",
+                        detector.size, first
+                    ),
+                    (positions, true) => format!(
+                        "Markers of {} distinct characters end at {}",
+                        detector.size,
+                        positions.iter().join(", ")
+                    ),
+                },
+            )
+            .join("\n")
+            .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{property, Rng};
+    use std::{collections::BTreeSet, io::Cursor};
+
+    const SAMPLE: &str = include_str!("../day06/sample.txt");
+
+    fn generate_input(rng: &mut Rng, size: usize) -> ParseOutput {
+        let letters = ('a'..='z').collect::<Vec<_>>();
+        (0..rng.usize_in(1..=size))
+            .map(|_| *rng.choose(&letters))
+            .collect()
+    }
+
+    #[test]
+    fn parser_requires_single_line() {
+        assert!(parse_file("abcd\nefgh\n".to_string()).is_err());
+    }
+
+    fn arguments(n: &[usize], all: bool) -> CommandLineArguments {
+        CommandLineArguments { n: n.to_vec(), all }
+    }
+
+    fn window_markers(input: &[char], size: usize) -> Vec<usize> {
+        input
+            .windows(size)
+            .enumerate()
+            .filter(|(_, chars)| chars.iter().collect::<BTreeSet<_>>().len() == size)
+            .map(|(position, _)| position + size)
+            .collect()
+    }
+
+    #[test]
+    fn sample_finds_packet_and_message_markers_in_one_pass() {
+        let signal = parse_file(SAMPLE.to_string()).expect("Sample parses");
+
+        assert_eq!(
+            run(signal.clone(), arguments(&[4], false))
+                .unwrap()
+                .to_string(),
+            "7"
+        );
+        assert_eq!(
+            run(signal, arguments(&[4, 14], false))
+                .unwrap()
+                .to_string(),
+            "Marker of 4 distinct characters ends at 7\nMarker of 14 distinct characters ends at 19"
+        );
+    }
+
+    #[test]
+    fn stream_finds_every_marker() {
+        let reader = Box::new(Cursor::new("abcabd\n"));
+
+        assert_eq!(
+            run_stream(reader, arguments(&[3, 4, 5], true))
+                .unwrap()
+                .to_string(),
+            "Markers of 3 distinct characters end at 3, 4, 5, 6\nMarkers of 4 distinct characters end at 6\nNo marker of 5 distinct characters"
+        );
+        assert!(run_stream(Box::new(Cursor::new("aaaa")), arguments(&[2], false)).is_err());
+        assert!(run_stream(Box::new(Cursor::new("ab\u{e9}")), arguments(&[2], true)).is_err());
+        assert!(run_stream(Box::new(Cursor::new("abcd\nxyz\n")), arguments(&[4], false)).is_err());
+        assert!(run_stream(
+            Box::new(Cursor::new("abcd\n")),
+            arguments(&[usize::MAX], true)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn streamed_signal_accepts_what_the_parser_accepts() {
+        [
+            "abcd\n",
+            "abcd",
+            "abcd\r\n",
+            "",
+            "\n",
+            "abcd\nefgh\n",
+            "abcd\n\n",
+            "ABCD EFG\nxyz\n",
+            "abcd efg\n",
+            "abcd\r",
+            "ab\rcd\n",
+        ]
+        .iter()
+        .for_each(|input| {
+            let parsed = parse_file(input.to_string())
+                .and_then(|signal| run(signal, arguments(&[2], true)))
+                .map(|result| result.to_string());
+            let streamed = run_stream(Box::new(Cursor::new(*input)), arguments(&[2], true))
+                .map(|result| result.to_string());
+
+            assert_eq!(parsed.ok(), streamed.ok(), "{:?}", input);
+        });
+    }
+
+    #[test]
+    fn rolling_counts_match_every_window() {
+        property(100, 200, |rng, size| {
+            let input = generate_input(rng, size);
+            let sizes = [1, 2, 4, 14];
+            let detectors =
+                find_markers(input.iter().cloned().map(Ok), &arguments(&sizes, true)).unwrap();
+
+            detectors.iter().for_each(|detector| {
+                assert_eq!(detector.positions, window_markers(&input, detector.size));
+            });
+        });
+    }
 }
@@ -1,65 +1,89 @@
-use adventofcode2022::{parse_lines, parse_usize, single_arg, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{parse_lines, parse_usize, Command, CommandResult, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::end,
     primitive::{just, take_until},
     text, Parser,
 };
-use clap::ArgMatches;
-use std::cell::LazyCell;
+use clap::{Args, ValueHint};
+use itertools::Itertools;
+use std::{
+    cell::LazyCell,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    iter::once,
+    path::{Path, PathBuf},
+};
 
 type ParseOutput = Vec<TerminalOutput>;
 
 pub const DAY_07: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let threshold = single_arg("threshold", 't', "The largest directory to sum.")
-        .value_parser(clap::value_parser!(usize));
-    let space = single_arg(
-        "space",
-        's',
-        "The space required to free up in the file system",
-    )
-    .value_parser(clap::value_parser!(usize))
-    .conflicts_with("threshold");
     let problem = Problem::new(
         "day07",
         "Reads terminal output then gives stats on the file size for folders found in the terminal output.",
         "Path to the input file. The output of one terminal session of the elf computer.",
-        vec![threshold, space],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { find_strategy: FindStrategy::SumThreshold { threshold: 100_000 }}, "Finds all the folder with size less than 100_000 and sums their total.")
-    .with_part2(CommandLineArguments { find_strategy: FindStrategy::MinFree { space_needed: 30_000_000 } }, "Finds the smallest directory to delete to make space for 30_000_000 bytes.");
+    .with_source(load_directory)
+    .with_part1(CommandLineArguments { find_strategy: FindStrategyArgs { threshold: Some(100_000), ..Default::default() }, path: "/".to_string(), directory: None }, "Finds all the folder with size less than 100_000 and sums their total.")
+    .with_part2(CommandLineArguments { find_strategy: FindStrategyArgs { space: Some(30_000_000), ..Default::default() }, path: "/".to_string(), directory: None }, "Finds the smallest directory to delete to make space for 30_000_000 bytes.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    find_strategy: FindStrategy,
+    #[command(flatten)]
+    find_strategy: FindStrategyArgs,
+    #[arg(
+        short,
+        long,
+        value_name = "PATH",
+        default_value = "/",
+        help = "The directory to run the strategy on, e.g. /a/e"
+    )]
+    path: String,
+    #[arg(
+        short,
+        long,
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        conflicts_with = "file",
+        help = "Walks a local directory and uses its elf terminal transcript instead of the input file"
+    )]
+    directory: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FileCommand {
     ChangeDirectory(DirectoryDirection),
     List,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DirectoryDirection {
     Root,
     Down(String),
     Up,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ElfFile {
     Directory(String),
     File(String, usize),
 }
 
-#[derive(Debug, Clone)]
+impl ElfFile {
+    fn name(&self) -> &str {
+        match self {
+            ElfFile::Directory(name) | ElfFile::File(name, _) => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TerminalOutput {
     FileCommand(FileCommand),
     ElfFile(ElfFile),
@@ -69,25 +93,75 @@ pub enum TerminalOutput {
 pub enum FindStrategy {
     SumThreshold { threshold: usize },
     MinFree { space_needed: usize },
+    Largest { count: usize },
+    Glob { pattern: String },
+    Tree,
+    DiskUsage,
+    Check,
+    Emit,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    let threshold =
-        args.get_one::<usize>("threshold")
-            .map(|threshold| FindStrategy::SumThreshold {
-                threshold: *threshold,
-            });
-    let space = args
-        .get_one::<usize>("space")
-        .map(|space| FindStrategy::MinFree {
-            space_needed: *space,
-        });
-    let find_strategy = match (threshold, space) {
-        (Some(threshold), None) => threshold,
-        (None, Some(space)) => space,
-        _ => unreachable!(),
-    };
-    CommandLineArguments { find_strategy }
+#[derive(Debug, Clone, Default, Args)]
+#[group(multiple = false, required = true)]
+pub struct FindStrategyArgs {
+    #[arg(short, long, help = "The largest directory to sum.")]
+    threshold: Option<usize>,
+    #[arg(short, long, help = "The space required to free up in the file system")]
+    space: Option<usize>,
+    #[arg(short, long, help = "Lists the largest directories by size")]
+    largest: Option<usize>,
+    #[arg(
+        short,
+        long,
+        help = "Lists the files whose name matches a glob, or whose path does when the glob has a /"
+    )]
+    glob: Option<String>,
+    #[arg(short = 'T', long, help = "Draws the directory tree sorted by size")]
+    tree: bool,
+    #[arg(
+        short = 'u',
+        long,
+        help = "Lists every directory with its size like du"
+    )]
+    du: bool,
+    #[arg(
+        short,
+        long,
+        help = "Reports directories listed twice with different contents and cds into unlisted directories"
+    )]
+    check: bool,
+    #[arg(short, long, help = "Prints the elf terminal transcript of the input")]
+    emit: bool,
+}
+
+impl TryFrom<FindStrategyArgs> for FindStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from(args: FindStrategyArgs) -> Result<Self> {
+        Ok(match args {
+            FindStrategyArgs {
+                threshold: Some(threshold),
+                ..
+            } => FindStrategy::SumThreshold { threshold },
+            FindStrategyArgs {
+                space: Some(space_needed),
+                ..
+            } => FindStrategy::MinFree { space_needed },
+            FindStrategyArgs {
+                largest: Some(count),
+                ..
+            } => FindStrategy::Largest { count },
+            FindStrategyArgs {
+                glob: Some(pattern),
+                ..
+            } => FindStrategy::Glob { pattern },
+            FindStrategyArgs { tree: true, .. } => FindStrategy::Tree,
+            FindStrategyArgs { du: true, .. } => FindStrategy::DiskUsage,
+            FindStrategyArgs { check: true, .. } => FindStrategy::Check,
+            FindStrategyArgs { emit: true, .. } => FindStrategy::Emit,
+            _ => return Err(anyhow!("A find strategy is required")),
+        })
+    }
 }
 
 fn parse_file(file: String) -> Result<ParseOutput> {
@@ -123,6 +197,76 @@ fn parse_directory_direction() -> impl Parser<char, DirectoryDirection, Error =
     just("cd ").ignore_then(root.or(up).or(down))
 }
 
+fn format_terminal_output(output: &TerminalOutput) -> String {
+    match output {
+        TerminalOutput::FileCommand(FileCommand::List) => "$ ls".to_string(),
+        TerminalOutput::FileCommand(FileCommand::ChangeDirectory(direction)) => match direction {
+            DirectoryDirection::Root => "$ cd /".to_string(),
+            DirectoryDirection::Up => "$ cd ..".to_string(),
+            DirectoryDirection::Down(name) => format!("$ cd {}", name),
+        },
+        TerminalOutput::ElfFile(ElfFile::Directory(name)) => format!("dir {}", name),
+        TerminalOutput::ElfFile(ElfFile::File(name, size)) => format!("{} {}", size, name),
+    }
+}
+
+fn format_input(input: &ParseOutput) -> String {
+    input.iter().map(format_terminal_output).join("\n")
+}
+
+fn transcript(directory: &Path) -> Result<ParseOutput> {
+    let mut output = vec![TerminalOutput::FileCommand(FileCommand::ChangeDirectory(
+        DirectoryDirection::Root,
+    ))];
+    walk_directory(directory, &mut output)?;
+    Ok(output)
+}
+
+fn walk_directory(directory: &Path, output: &mut ParseOutput) -> Result<()> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry
+                .file_name()
+                .into_string()
+                .ok()
+                .filter(|name| !name.contains(['\n', '\r']))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} has a name the elf terminal cannot show",
+                        entry.path().display()
+                    )
+                })?;
+            Ok((name, metadata))
+        })
+        .filter_ok(|(_, metadata)| metadata.is_dir() || metadata.is_file())
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    output.push(TerminalOutput::FileCommand(FileCommand::List));
+    output.extend(entries.iter().map(|(name, metadata)| {
+        TerminalOutput::ElfFile(match metadata.is_dir() {
+            true => ElfFile::Directory(name.clone()),
+            false => ElfFile::File(name.clone(), metadata.len() as usize),
+        })
+    }));
+
+    entries
+        .iter()
+        .filter(|(_, metadata)| metadata.is_dir())
+        .try_for_each(|(name, _)| {
+            output.push(TerminalOutput::FileCommand(FileCommand::ChangeDirectory(
+                DirectoryDirection::Down(name.clone()),
+            )));
+            walk_directory(&directory.join(name), output)?;
+            output.push(TerminalOutput::FileCommand(FileCommand::ChangeDirectory(
+                DirectoryDirection::Up,
+            )));
+            Ok(())
+        })
+}
+
 fn parse_elf_file() -> impl Parser<char, ElfFile, Error = Simple<char>> {
     parse_efile().or(parse_directory())
 }
@@ -141,9 +285,12 @@ fn parse_directory() -> impl Parser<char, ElfFile, Error = Simple<char>> {
         .map(|(name, _)| ElfFile::Directory(name.into_iter().collect()))
 }
 
+const ROOT: usize = 0;
+
 #[derive(Debug)]
 struct Arena {
     files: Vec<FileSystem>,
+    inconsistencies: Vec<Inconsistency>,
 }
 
 #[derive(Debug, Clone)]
@@ -153,111 +300,600 @@ struct FileSystem {
     children: Vec<usize>,
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    let root = FileSystem {
-        file: ElfFile::Directory("/".to_string()),
-        parent: None,
-        children: Vec::new(),
-    };
-    let mut arena = Arena { files: vec![root] };
-    let mut current = 0usize;
-
-    input.into_iter().for_each(|output| match output {
-        TerminalOutput::ElfFile(file) => {
-            if arena
-                .files
-                .get(current)
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inconsistency {
+    UnlistedDirectory { line: usize, path: String },
+    ChangedListing { line: usize, path: String },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inconsistency::UnlistedDirectory { line, path } => {
+                write!(f, "Line {}: cd into unknown directory {}", line, path)
+            }
+            Inconsistency::ChangedListing { line, path } => {
+                write!(
+                    f,
+                    "Line {}: {} was listed again with different contents",
+                    line, path
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Listing {
+    directory: usize,
+    line: usize,
+    entries: HashSet<ElfFile>,
+}
+
+impl Arena {
+    fn from_terminal(input: &[TerminalOutput]) -> Result<Arena> {
+        let root = FileSystem {
+            file: ElfFile::Directory("/".to_string()),
+            parent: None,
+            children: Vec::new(),
+        };
+        let mut arena = Arena {
+            files: vec![root],
+            inconsistencies: Vec::new(),
+        };
+        let mut listings = HashMap::new();
+        let mut listing: Option<Listing> = None;
+        let mut current = ROOT;
+
+        for (index, output) in input.iter().enumerate() {
+            let line = index + 1;
+            match output {
+                TerminalOutput::ElfFile(file) => {
+                    if let Some(listing) = listing.as_mut() {
+                        listing.entries.insert(file.clone());
+                    }
+                    arena.add_child(current, file.clone());
+                }
+                TerminalOutput::FileCommand(command) => {
+                    if let Some(finished) = listing.take() {
+                        arena.record_listing(&mut listings, finished);
+                    }
+                    match command {
+                        FileCommand::List => {
+                            listing = Some(Listing {
+                                directory: current,
+                                line,
+                                entries: HashSet::new(),
+                            })
+                        }
+                        FileCommand::ChangeDirectory(DirectoryDirection::Root) => current = ROOT,
+                        FileCommand::ChangeDirectory(DirectoryDirection::Up) => {
+                            current = arena
+                                .get(current)
+                                .parent
+                                .ok_or_else(|| anyhow!("Cannot cd .. from the root directory"))?
+                        }
+                        FileCommand::ChangeDirectory(DirectoryDirection::Down(name)) => {
+                            current = match arena.child_directory(current, name) {
+                                Some(child) => child,
+                                None => {
+                                    let child =
+                                        arena.add_child(current, ElfFile::Directory(name.clone()));
+                                    arena
+                                        .inconsistencies
+                                        .push(Inconsistency::UnlistedDirectory {
+                                            line,
+                                            path: arena.path(child),
+                                        });
+                                    child
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(finished) = listing {
+            arena.record_listing(&mut listings, finished);
+        }
+
+        Ok(arena)
+    }
+
+    fn get(&self, index: usize) -> &FileSystem {
+        self.files.get(index).expect("valid index")
+    }
+
+    fn is_directory(&self, index: usize) -> bool {
+        matches!(self.get(index).file, ElfFile::Directory(_))
+    }
+
+    fn add_child(&mut self, directory: usize, file: ElfFile) -> usize {
+        let existing = self
+            .get(directory)
+            .children
+            .iter()
+            .find(|child| self.get(**child).file == file)
+            .cloned();
+
+        existing.unwrap_or_else(|| {
+            self.files.push(FileSystem {
+                file,
+                parent: Some(directory),
+                children: Vec::new(),
+            });
+            let new_index = self.files.len() - 1;
+            self.files
+                .get_mut(directory)
                 .expect("valid index")
                 .children
-                .iter()
-                .find(|child| arena.files.get(**child).expect("valid index").file == file)
-                .is_none()
-            {
-                let new_file = FileSystem {
-                    file: file,
-                    parent: Some(current),
-                    children: Vec::new(),
-                };
-                arena.files.push(new_file);
-                let new_index = arena.files.len() - 1;
-                arena
-                    .files
-                    .get_mut(current)
-                    .expect("valid index")
-                    .children
-                    .push(new_index);
+                .push(new_index);
+            new_index
+        })
+    }
+
+    fn record_listing(
+        &mut self,
+        listings: &mut HashMap<usize, HashSet<ElfFile>>,
+        listing: Listing,
+    ) {
+        match listings.get(&listing.directory) {
+            Some(previous) if previous != &listing.entries => {
+                self.inconsistencies.push(Inconsistency::ChangedListing {
+                    line: listing.line,
+                    path: self.path(listing.directory),
+                })
+            }
+            Some(_) => (),
+            None => {
+                listings.insert(listing.directory, listing.entries);
             }
         }
-        TerminalOutput::FileCommand(command) => match command {
-            FileCommand::List => (),
-            FileCommand::ChangeDirectory(direction) => match direction {
-                DirectoryDirection::Root => current = 0usize,
-                DirectoryDirection::Down(file_name) => {
-                    let child = arena
-                        .files
-                        .get(current)
-                        .expect("valid index")
-                        .children
-                        .iter()
-                        .find(
-                            |child| match &arena.files.get(**child).expect("valid index").file {
-                                ElfFile::Directory(name) => name == &file_name,
-                                _ => false,
-                            },
-                        )
-                        .expect("valid cd");
-                    current = *child
-                }
-                DirectoryDirection::Up => {
-                    current = arena
-                        .files
-                        .get(current)
-                        .expect("valid index")
-                        .parent
-                        .expect("valid parent")
+    }
+
+    fn child(&self, directory: usize, name: &str) -> Option<usize> {
+        self.get(directory)
+            .children
+            .iter()
+            .find(|child| self.get(**child).file.name() == name)
+            .cloned()
+    }
+
+    fn child_directory(&self, directory: usize, name: &str) -> Option<usize> {
+        self.get(directory)
+            .children
+            .iter()
+            .find(|child| match &self.get(**child).file {
+                ElfFile::Directory(child_name) => child_name == name,
+                _ => false,
+            })
+            .cloned()
+    }
+
+    fn lookup(&self, path: &str) -> Option<usize> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(ROOT, |directory, name| self.child(directory, name))
+    }
+
+    fn path(&self, index: usize) -> String {
+        let mut names = Vec::new();
+        let mut current = index;
+        while let Some(parent) = self.get(current).parent {
+            names.push(self.get(current).file.name());
+            current = parent;
+        }
+
+        once("").chain(names.into_iter().rev()).join("/") + if index == ROOT { "/" } else { "" }
+    }
+
+    fn descendants(&self, index: usize) -> Vec<usize> {
+        let mut stack = vec![index];
+        let mut descendants = Vec::new();
+        while let Some(current) = stack.pop() {
+            descendants.push(current);
+            stack.extend(self.get(current).children.iter().rev());
+        }
+        descendants
+    }
+
+    fn directories(&self, index: usize) -> Vec<usize> {
+        self.descendants(index)
+            .into_iter()
+            .filter(|child| self.is_directory(*child))
+            .collect()
+    }
+
+    fn disk_usages(&self) -> Result<Vec<usize>> {
+        let mut sizes = self
+            .files
+            .iter()
+            .map(|node| match node.file {
+                ElfFile::File(_, size) => size,
+                ElfFile::Directory(_) => 0,
+            })
+            .collect::<Vec<_>>();
+
+        for index in (1..self.files.len()).rev() {
+            if let Some(parent) = self.get(index).parent {
+                sizes[parent] = sizes[parent].checked_add(sizes[index]).ok_or_else(|| {
+                    anyhow!("The files in {} are too large to add up", self.path(parent))
+                })?;
+            }
+        }
+        Ok(sizes)
+    }
+
+    fn tree(&self, index: usize, sizes: &[usize]) -> Vec<String> {
+        let node = self.get(index);
+        let description = match &node.file {
+            ElfFile::Directory(name) => format!("- {} (dir, size={})", name, sizes[index]),
+            ElfFile::File(name, size) => format!("- {} (file, size={})", name, size),
+        };
+
+        once(description)
+            .chain(
+                node.children
+                    .iter()
+                    .sorted_by_key(|child| (Reverse(sizes[**child]), self.get(**child).file.name()))
+                    .flat_map(|child| self.tree(*child, sizes))
+                    .map(|line| format!("  {}", line)),
+            )
+            .collect()
+    }
+
+    fn usage_report(&self, indices: impl Iterator<Item = usize>, sizes: &[usize]) -> String {
+        indices
+            .map(|index| format!("{}\t{}", sizes[index], self.path(index)))
+            .join("\n")
+    }
+}
+
+// Neither * nor ? match a /, so every / in the name has to line up with one in the pattern and
+// the segments between them can be matched on their own.
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    let patterns = pattern.split(|c| *c == '/').collect::<Vec<_>>();
+    let names = name.split(|c| *c == '/').collect::<Vec<_>>();
+
+    patterns.len() == names.len()
+        && patterns
+            .iter()
+            .zip(names.iter())
+            .all(|(pattern, name)| segment_matches(pattern, name))
+}
+
+// On a mismatch only the most recent * takes one more character, so there is no backtracking
+// into earlier stars.
+fn segment_matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(expected) if *expected == '?' || *expected == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
                 }
+                None => return false,
             },
-        },
-    });
+        }
+    }
 
-    let directory_sizes = arena
-        .files
-        .iter()
-        .filter(|file| match file.file {
-            ElfFile::Directory(_) => true,
-            _ => false,
-        })
-        .map(|dir| disk_usage(&arena, dir));
+    pattern[p..].iter().all(|c| *c == '*')
+}
 
-    match arguments.find_strategy {
-        FindStrategy::SumThreshold { threshold } => {
-            directory_sizes.filter(|value| value <= &threshold).sum()
+fn load_directory(arguments: &CommandLineArguments) -> Option<Result<ParseOutput>> {
+    arguments.directory.as_deref().map(transcript)
+}
+
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<CommandResult> {
+    find(&input, arguments.find_strategy.try_into()?, &arguments.path)
+}
+
+fn find(input: &ParseOutput, find_strategy: FindStrategy, path: &str) -> Result<CommandResult> {
+    let arena = Arena::from_terminal(input)?;
+
+    if !matches!(find_strategy, FindStrategy::Check | FindStrategy::Emit) {
+        if let Some(unlisted) = arena
+            .inconsistencies
+            .iter()
+            .find(|inconsistency| matches!(inconsistency, Inconsistency::UnlistedDirectory { .. }))
+        {
+            return Err(anyhow!("{}", unlisted));
         }
+    }
+
+    let start = arena
+        .lookup(path)
+        .filter(|index| arena.is_directory(*index))
+        .ok_or_else(|| anyhow!("No directory {}", path))?;
+    let sizes = arena.disk_usages()?;
+    let directory_sizes = arena
+        .directories(start)
+        .into_iter()
+        .map(|directory| sizes[directory]);
+    let largest_directories = || {
+        arena
+            .directories(start)
+            .into_iter()
+            .sorted_by_key(|directory| (Reverse(sizes[*directory]), arena.path(*directory)))
+    };
+
+    match find_strategy {
+        FindStrategy::SumThreshold { threshold } => directory_sizes
+            .filter(|value| value <= &threshold)
+            .try_fold(0usize, |total, value| total.checked_add(value))
+            .map(|total| total.into())
+            .ok_or_else(|| {
+                anyhow!(
+                    "The directories under {} are too large to add up",
+                    threshold
+                )
+            }),
         FindStrategy::MinFree { space_needed } => {
             let max = 70_000_000_usize;
-            let current = disk_usage(&arena, &arena.files.get(0).expect("Root exists"));
-            let space_needed = space_needed - (max - current);
+            let current = sizes[ROOT];
+            let free = max.checked_sub(current).ok_or_else(|| {
+                anyhow!("Files use {} which exceeds the disk size {}", current, max)
+            })?;
+            let space_needed = space_needed.saturating_sub(free);
 
-            let mut big_dirs = directory_sizes
+            directory_sizes
                 .filter(|value| value >= &space_needed)
-                .collect::<Vec<usize>>();
-            big_dirs.sort();
-            *big_dirs.get(0).expect("At least one valid dir")
+                .min()
+                .map(|size| size.into())
+                .ok_or_else(|| anyhow!("No directory frees up {}", space_needed))
         }
+        FindStrategy::Largest { count } => Ok(arena
+            .usage_report(largest_directories().take(count), &sizes)
+            .into()),
+        FindStrategy::DiskUsage => Ok(arena.usage_report(largest_directories(), &sizes).into()),
+        FindStrategy::Tree => Ok(arena.tree(start, &sizes).join("\n").into()),
+        FindStrategy::Glob { pattern } => {
+            let pattern = pattern.chars().collect::<Vec<_>>();
+            let matches = arena
+                .descendants(start)
+                .into_iter()
+                .filter(|index| !arena.is_directory(*index))
+                .filter(|index| {
+                    let target = if pattern.contains(&'/') {
+                        arena.path(*index)
+                    } else {
+                        arena.get(*index).file.name().to_string()
+                    };
+                    glob_matches(&pattern, &target.chars().collect::<Vec<_>>())
+                })
+                .sorted_by_key(|index| arena.path(*index));
+
+            Ok(arena.usage_report(matches, &sizes).into())
+        }
+        FindStrategy::Check => Ok(match arena.inconsistencies.as_slice() {
+            [] => "No inconsistencies found".to_string(),
+            inconsistencies => inconsistencies.iter().join("\n"),
+        }
+        .into()),
+        FindStrategy::Emit => Ok(format_input(input).into()),
     }
 }
 
-fn disk_usage(arena: &Arena, directory: &FileSystem) -> usize {
-    directory
-        .children
-        .iter()
-        .map(|file_index| {
-            let child = arena.files.get(*file_index).expect("Valid index");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::FromArgMatches;
+    use std::{env, iter::once, process};
 
-            match child.file {
-                ElfFile::Directory(_) => disk_usage(arena, child),
-                ElfFile::File(_, len) => len,
-            }
-        })
-        .sum()
+    const SAMPLE: &str = include_str!("../day07/sample.txt");
+
+    #[test]
+    fn parse_elf_file_reads_files_and_directories() {
+        assert_eq!(
+            parse_elf_file().parse("14848514 b.txt\n"),
+            Ok(ElfFile::File("b.txt".to_string(), 14848514))
+        );
+        assert_eq!(
+            parse_elf_file().parse("dir a\n"),
+            Ok(ElfFile::Directory("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_directory_direction_reads_targets() {
+        assert_eq!(
+            parse_directory_direction().parse("cd /"),
+            Ok(DirectoryDirection::Root)
+        );
+        assert_eq!(
+            parse_directory_direction().parse("cd .."),
+            Ok(DirectoryDirection::Up)
+        );
+        assert_eq!(
+            parse_directory_direction().parse("cd abc\n"),
+            Ok(DirectoryDirection::Down("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn parser_round_trips_sample() {
+        let output = parse_file(SAMPLE.to_string()).expect("Sample parses");
+
+        assert_eq!(format_input(&output), SAMPLE.trim_end());
+    }
+
+    #[test]
+    #[allow(clippy::borrow_interior_mutable_const)]
+    fn find_strategy_arguments_are_exclusive() {
+        let matches = |args: &[&str]| {
+            DAY_07
+                .get_subcommand()
+                .try_get_matches_from(["day07", "-f", "input.txt"].iter().chain(args))
+        };
+
+        assert!(matches(&["-t", "100", "-s", "300"]).is_err());
+        assert!(matches(&[]).is_err());
+        let space = matches(&["-s", "300"]).unwrap();
+        assert!(matches!(
+            CommandLineArguments::from_arg_matches(&space)
+                .unwrap()
+                .find_strategy
+                .try_into(),
+            Ok(FindStrategy::MinFree { space_needed: 300 })
+        ));
+        assert!(FindStrategy::try_from(FindStrategyArgs::default()).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::borrow_interior_mutable_const)]
+    fn parts_reject_a_directory() {
+        let matches = |args: &[&str]| {
+            DAY_07
+                .get_subcommand()
+                .try_get_matches_from(once(&"day07").chain(args))
+        };
+
+        assert!(matches(&["-d", ".", "part1"]).is_err());
+        assert!(matches(&["part2", "-d", "."]).is_err());
+        assert!(matches(&["part1"]).is_ok());
+    }
+
+    fn query(input: &str, find_strategy: FindStrategy, path: &str) -> Result<String> {
+        find(&parse_file(input.to_string())?, find_strategy, path).map(|result| result.to_string())
+    }
+
+    #[test]
+    fn oversized_directories_are_an_error() {
+        let input = "$ cd /\n$ ls\n18446744073709551615 a\n1 b\n";
+
+        assert_eq!(
+            query(input, FindStrategy::DiskUsage, "/")
+                .unwrap_err()
+                .to_string(),
+            "The files in / are too large to add up"
+        );
+        assert!(query(
+            "$ cd /\n$ ls\ndir a\n18446744073709551615 b\n$ cd a\n$ ls\n1 c\n",
+            FindStrategy::SumThreshold {
+                threshold: usize::MAX
+            },
+            "/a"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn tree_is_sorted_by_size() {
+        let tree = query(SAMPLE, FindStrategy::Tree, "/a").unwrap();
+
+        assert_eq!(
+            tree,
+            "- a (dir, size=94853)
+  - h.lst (file, size=62596)
+  - f (file, size=29116)
+  - g (file, size=2557)
+  - e (dir, size=584)
+    - i (file, size=584)"
+        );
+    }
+
+    #[test]
+    fn queries_find_directories_and_files() {
+        assert_eq!(
+            query(SAMPLE, FindStrategy::Largest { count: 2 }, "/").unwrap(),
+            "48381165\t/\n24933642\t/d"
+        );
+        assert_eq!(
+            query(SAMPLE, FindStrategy::DiskUsage, "/a/").unwrap(),
+            "94853\t/a\n584\t/a/e"
+        );
+        let glob = |pattern: &str| {
+            query(
+                SAMPLE,
+                FindStrategy::Glob {
+                    pattern: pattern.to_string(),
+                },
+                "/",
+            )
+            .unwrap()
+        };
+        assert_eq!(glob("d.*"), "5626152\t/d/d.ext\n8033020\t/d/d.log");
+        assert_eq!(glob("/a/?"), "29116\t/a/f\n2557\t/a/g");
+        assert_eq!(glob("/*/*/i"), "584\t/a/e/i");
+        assert_eq!(glob("*.*.*"), "");
+        assert!(query(SAMPLE, FindStrategy::Tree, "/b.txt").is_err());
+    }
+
+    #[test]
+    fn glob_matching_handles_many_stars() {
+        let glob = |pattern: &str, name: &str| {
+            glob_matches(
+                &pattern.chars().collect::<Vec<_>>(),
+                &name.chars().collect::<Vec<_>>(),
+            )
+        };
+        let long_name = "a".repeat(200);
+
+        assert!(!glob("*a*a*a*a*a*a*a*b", &long_name));
+        assert!(glob("*a*a*a*a*a*a*a*", &long_name));
+        assert!(glob("a*c?e", "abbbcde"));
+        assert!(!glob("a*", "ab/c"));
+        assert!(glob("a*/*", "ab/c"));
+        assert!(glob("", ""));
+        assert!(!glob("?", ""));
+    }
+
+    #[test]
+    fn check_reports_inconsistent_logs() {
+        let input =
+            "$ cd /\n$ ls\ndir a\n1 x\n$ cd b\n$ ls\n2 y\n$ cd /\n$ ls\n1 x\ndir a\n$ ls\n3 z\n";
+
+        assert_eq!(
+            query(input, FindStrategy::Check, "/").unwrap(),
+            "Line 5: cd into unknown directory /b\nLine 12: / was listed again with different contents"
+        );
+        assert_eq!(
+            query(SAMPLE, FindStrategy::Check, "/").unwrap(),
+            "No inconsistencies found"
+        );
+        assert!(query(input, FindStrategy::DiskUsage, "/").is_err());
+    }
+
+    #[test]
+    #[allow(clippy::borrow_interior_mutable_const)]
+    fn transcript_of_a_directory_matches_its_disk_usage() {
+        let root = env::temp_dir().join(format!("day07-{}", process::id()));
+        fs::create_dir_all(root.join("a/e")).unwrap();
+        fs::create_dir_all(root.join("d")).unwrap();
+        [("a/e/i", 584), ("a/f", 29116), ("b.txt", 1234), ("d/j", 40)]
+            .iter()
+            .for_each(|(file, size)| fs::write(root.join(file), vec![b'x'; *size]).unwrap());
+        let run_day = |args: &[&str]| {
+            let directory = root.to_str().unwrap();
+            DAY_07
+                .get_subcommand()
+                .try_get_matches_from(["day07", "-d", directory].iter().chain(args))
+                .map_err(anyhow::Error::from)
+                .and_then(|matches| DAY_07.run(&matches))
+                .map(|result| result.to_string())
+        };
+
+        let input = transcript(&root).unwrap();
+        let usage = run_day(&["--du"]);
+        let checked = run_day(&["--check"]);
+        let with_file = run_day(&["-f", "input.txt", "--du"]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(with_file.is_err());
+        assert_eq!(parse_file(format_input(&input) + "\n").ok(), Some(input));
+        assert_eq!(usage.unwrap(), "30974\t/\n29700\t/a\n584\t/a/e\n40\t/d");
+        assert_eq!(checked.unwrap(), "No inconsistencies found");
+    }
 }
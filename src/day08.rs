@@ -1,24 +1,19 @@
 use adventofcode2022::{
-    parse_lines, parse_usize, single_arg, BoundedPoint, Command, ParseError, PointDirection,
-    Problem,
+    parse_grid, parse_usize, BoundedPoint, Command, ParseError, PointDirection, Problem,
 };
 use anyhow::Result;
 use chumsky::{prelude::Simple, primitive::end, primitive::one_of, Parser};
-use clap::{ArgMatches, ValueEnum};
+use clap::{Args, ValueEnum};
 use itertools::Itertools;
 use std::cell::LazyCell;
 
 type ParseOutput = Vec<Vec<usize>>;
 
 pub const DAY_08: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let survey = single_arg("survey", 's', "The type of survey to preform")
-        .value_parser(clap::value_parser!(Survey));
     let problem = Problem::new(
         "day08",
         "Servey's a forest and gives stats about the visibility of trees in the forest",
         "Path to the input file. Should consist of lines of and equal number of integers between 0-9",
-        vec![survey],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -33,20 +28,12 @@ pub enum Survey {
     BestTree,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The type of survey to preform")]
     survey: Survey,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        survey: args
-            .get_one::<Survey>("survey")
-            .expect("Valid arguments")
-            .clone(),
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -56,16 +43,11 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
     let digits = ('0'..='9').into_iter().collect::<String>();
 
-    parse_lines(
-        one_of(digits)
-            .try_map(|value: char, span| {
-                parse_usize()
-                    .parse(value.to_string())
-                    .map_err(|op| Simple::custom(span, op.into_iter().join("\n")))
-            })
-            .repeated()
-            .at_least(1),
-    )
+    parse_grid(one_of(digits).try_map(|value: char, span| {
+        parse_usize()
+            .parse(value.to_string())
+            .map_err(|op| Simple::custom(span, op.into_iter().join("\n")))
+    }))
     .then_ignore(end())
 }
 
@@ -151,3 +133,13 @@ fn is_visible_direction(
             < height
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_rejects_non_digits() {
+        assert!(parse_file("123\n4a6".to_string()).is_err());
+    }
+}
@@ -1,26 +1,24 @@
 use adventofcode2022::{
-    absolute_difference, parse_isize, parse_lines, single_arg, Command, ParseError, Problem,
+    absolute_difference, parse_isize, parse_lines, Command, ParseError, Problem,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just, one_of},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use std::{cell::LazyCell, collections::BTreeSet};
 
 type ParseOutput = Vec<Direction>;
 
+const MAX_STEPS: usize = 1_000_000;
+
 pub const DAY_09: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let length = single_arg("length", 'l', "The length of the rope")
-        .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day09",
         "Moves a rope along a path then outputs the number of unqiue positions for the rope's tail.",
         "Path to the input file. Each line should contain a direction for the rope to travel followed by a distance for the rope to travel.",
-        vec![length],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -29,12 +27,13 @@ pub const DAY_09: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The length of the rope")]
     length: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Direction {
     Up(isize),
     Right(isize),
@@ -42,12 +41,6 @@ pub enum Direction {
     Left(isize),
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        length: *args.get_one::<usize>("length").expect("Valid arguments"),
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -71,7 +64,19 @@ fn parse_direction() -> impl Parser<char, Direction, Error = Simple<char>> {
         })
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    input
+        .iter()
+        .map(|direction| match direction {
+            Direction::Up(count)
+            | Direction::Right(count)
+            | Direction::Down(count)
+            | Direction::Left(count) => count.unsigned_abs(),
+        })
+        .try_fold(0usize, |total, count| total.checked_add(count))
+        .filter(|total| *total <= MAX_STEPS)
+        .ok_or_else(|| anyhow!("The path moves the rope more than {} steps", MAX_STEPS))?;
+
     let mut movements = BTreeSet::new();
     let mut chain = vec![(0isize, 0isize); arguments.length];
 
@@ -109,5 +114,34 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
         }
     }
 
-    movements.len()
+    Ok(movements.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_direction_reads_each_direction() {
+        assert_eq!(parse_direction().parse("U 4"), Ok(Direction::Up(4)));
+        assert_eq!(parse_direction().parse("R 12"), Ok(Direction::Right(12)));
+        assert_eq!(parse_direction().parse("D 1"), Ok(Direction::Down(1)));
+        assert_eq!(parse_direction().parse("L 3"), Ok(Direction::Left(3)));
+        assert!(parse_direction().parse("X 3").is_err());
+    }
+
+    #[test]
+    fn run_rejects_paths_longer_than_the_step_limit() {
+        let input = vec![Direction::Right(1), Direction::Up(MAX_STEPS as isize)];
+
+        assert!(run(input, CommandLineArguments { length: 2 }).is_err());
+        assert_eq!(
+            run(
+                vec![Direction::Up(MAX_STEPS as isize)],
+                CommandLineArguments { length: 2 }
+            )
+            .unwrap(),
+            MAX_STEPS
+        );
+    }
 }
@@ -1,65 +1,60 @@
 use adventofcode2022::{
-    flag_arg, parse_isize, parse_lines, single_arg, Command, ParseError, Problem,
+    parse_isize, parse_lines, AsciiRenderer, Command, CommandResult, Grid, ParseError, Problem,
+    RenderCell, Renderer, Viewport,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
-use std::{cell::LazyCell, collections::BTreeSet};
+use clap::Args;
+use std::cell::LazyCell;
 
 type ParseOutput = Vec<Operation>;
 
+const SCREEN_WIDTH: usize = 40;
+
 pub const DAY_10: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let cycles = single_arg(
-        "cycles",
-        'c',
-        "The cycles to sample the signal strength from, comma delimited",
-    )
-    .value_delimiter(',')
-    .value_terminator(";")
-    .value_parser(clap::value_parser!(usize));
-    let render = flag_arg("render", 'r', "Whether to render the screen or not");
     let problem = Problem::new(
         "day10",
         "Finds the signal strength at each of the target cycles then sums them. Optionally prints the result of the crt scan lines.",
         "Path to the input file. File should contain lines of either addx [usize] or noop to render a sprite to a crt",
-        vec![cycles, render],
-        parse_arguments,
         parse_file,
         run,
     )
     .with_part1(
         CommandLineArguments {
-            target_cycles: BTreeSet::from([20, 60, 100, 140, 180, 220]),
+            target_cycles: vec![20, 60, 100, 140, 180, 220],
             render: false,
         },
         "Finds the signal strength for 20, 60, 100, 140, 180, and 220",
     )
-    .with_part2(CommandLineArguments { target_cycles: BTreeSet::new(), render: true }, "Prints the crt with no signal strength");
+    .with_part2(CommandLineArguments { target_cycles: vec![], render: true }, "Prints the crt with no signal strength");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    target_cycles: BTreeSet<usize>,
+    #[arg(
+        short = 'c',
+        long = "cycles",
+        value_name = "CYCLES",
+        required = true,
+        value_delimiter = ',',
+        value_terminator = ";",
+        help = "The cycles to sample the signal strength from, comma delimited"
+    )]
+    target_cycles: Vec<usize>,
+    #[arg(
+        short,
+        long,
+        help = "Returns the rendered screen, after the signal strength when cycles are given"
+    )]
     render: bool,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        target_cycles: args
-            .get_many::<usize>("cycles")
-            .expect("Valid arguments")
-            .cloned()
-            .collect(),
-        render: *args.get_one::<bool>("render").expect("Valid arguments"),
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operation {
     Noop,
     Addx(isize),
@@ -84,61 +79,150 @@ fn parse_operation() -> impl Parser<char, Operation, Error = Simple<char>> {
     noop.or(addx)
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<CommandResult> {
     let mut x = 1isize;
     let mut cycle_count = 0usize;
     let mut sum = 0isize;
-
-    input.into_iter().for_each(|operation| match operation {
-        Operation::Noop => {
-            update_cycle(&mut cycle_count, &mut sum, &x, &arguments);
-        }
-        Operation::Addx(value) => {
-            update_cycle(&mut cycle_count, &mut sum, &x, &arguments);
-            update_cycle(&mut cycle_count, &mut sum, &x, &arguments);
-            x += value;
+    let mut screen = Screen(Vec::new());
+
+    for (index, operation) in input.into_iter().enumerate() {
+        match operation {
+            Operation::Noop => {
+                update_cycle(&mut cycle_count, &mut sum, &mut screen, &x, &arguments)?;
+            }
+            Operation::Addx(value) => {
+                update_cycle(&mut cycle_count, &mut sum, &mut screen, &x, &arguments)?;
+                update_cycle(&mut cycle_count, &mut sum, &mut screen, &x, &arguments)?;
+                x = x.checked_add(value).ok_or_else(|| {
+                    anyhow!("Instruction {} moves the sprite out of range", index + 1)
+                })?;
+            }
         }
-    });
+    }
 
-    sum
+    Ok(
+        match (arguments.render, arguments.target_cycles.is_empty()) {
+            (true, true) => AsciiRenderer.render(&screen, None).into(),
+            (true, false) => format!("{}\n{}", sum, AsciiRenderer.render(&screen, None)).into(),
+            (false, _) => sum.into(),
+        },
+    )
 }
 
 fn update_cycle(
     cycle_count: &mut usize,
     sum: &mut isize,
+    screen: &mut Screen,
     x: &isize,
     arguments: &CommandLineArguments,
-) {
+) -> Result<()> {
     if arguments.render {
-        print_cycle(&cycle_count, &x);
+        screen.0.push(pixel_for_cycle(cycle_count, x));
     }
     *cycle_count += 1;
-    *sum += signal_strength_for_cycle(&cycle_count, &x, &arguments.target_cycles);
+    *sum = signal_strength_for_cycle(cycle_count, x, &arguments.target_cycles)
+        .and_then(|strength| sum.checked_add(strength))
+        .ok_or_else(|| anyhow!("The signal strength at cycle {} is too large", cycle_count))?;
+    Ok(())
 }
 
 fn signal_strength_for_cycle(
     cycle_count: &usize,
     x: &isize,
-    target_cycles: &BTreeSet<usize>,
-) -> isize {
-    target_cycles
-        .get(cycle_count)
-        .map(|target_cycle| *target_cycle as isize * x)
-        .unwrap_or(0)
+    target_cycles: &[usize],
+) -> Option<isize> {
+    if target_cycles.contains(cycle_count) {
+        isize::try_from(*cycle_count)
+            .ok()
+            .and_then(|cycle| cycle.checked_mul(*x))
+    } else {
+        Some(0)
+    }
+}
+
+fn pixel_for_cycle(cycle_count: &usize, x: &isize) -> Pixel {
+    let mod_cycle_count = (cycle_count % SCREEN_WIDTH) as isize;
+    if x.abs_diff(mod_cycle_count) <= 1 {
+        Pixel::Lit
+    } else {
+        Pixel::Dark
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Pixel {
+    Lit,
+    Dark,
 }
 
-fn print_cycle(cycle_count: &usize, x: &isize) {
-    let mod_cycle_count = (cycle_count % 40) as isize;
-    let print_value =
-        if *x == mod_cycle_count || x - 1 == mod_cycle_count || x + 1 == mod_cycle_count {
-            "#"
-        } else {
-            "."
+impl RenderCell for Pixel {
+    fn to_char(&self) -> char {
+        match self {
+            Pixel::Lit => '#',
+            Pixel::Dark => '.',
+        }
+    }
+}
+
+struct Screen(Vec<Pixel>);
+
+impl Grid for Screen {
+    type Cell = Pixel;
+
+    fn viewport(&self) -> Viewport {
+        Viewport {
+            min_x: 0,
+            min_y: 0,
+            max_x: SCREEN_WIDTH as isize - 1,
+            max_y: self.0.len().saturating_sub(1) as isize / SCREEN_WIDTH as isize,
+        }
+    }
+
+    fn cell(&self, x: isize, y: isize) -> Pixel {
+        self.0
+            .get(y as usize * SCREEN_WIDTH + x as usize)
+            .copied()
+            .unwrap_or(Pixel::Dark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adventofcode2022::{run_part, InputSource, Status};
+
+    #[test]
+    fn parse_operation_reads_each_operation() {
+        assert_eq!(parse_operation().parse("noop"), Ok(Operation::Noop));
+        assert_eq!(parse_operation().parse("addx 15"), Ok(Operation::Addx(15)));
+        assert_eq!(
+            parse_operation().parse("addx -11"),
+            Ok(Operation::Addx(-11))
+        );
+    }
+
+    #[test]
+    fn overflowing_register_is_an_error() {
+        let arguments = CommandLineArguments {
+            target_cycles: vec![20],
+            render: false,
         };
+        let input = parse_file("addx 9223372036854775807\naddx 5".to_string()).unwrap();
+
+        assert_eq!(
+            run(input, arguments).err().map(|error| error.to_string()),
+            Some("Instruction 1 moves the sprite out of range".to_string())
+        );
+    }
 
-    print!("{}", print_value);
+    #[test]
+    fn dashboard_shows_the_rendered_screen() {
+        let status = run_part(crate::commands, "day10", "part2", InputSource::Sample);
 
-    if mod_cycle_count == 39 {
-        println!();
+        assert!(matches!(
+            status,
+            Status::Answer(screen, _) if screen.starts_with("##..##..##..##..##..##..##..##..##..##..\n")
+                && screen.lines().count() == 6
+        ));
     }
 }
@@ -1,52 +1,36 @@
-use adventofcode2022::{
-    flag_arg, parse_between_blank_lines, parse_usize, single_arg, Command, ParseError, Problem,
-};
-use anyhow::Result;
+use adventofcode2022::{parse_between_blank_lines, parse_usize, Command, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     text, Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use std::cell::LazyCell;
 
 type ParseOutput = Vec<Monkey>;
 
 pub const DAY_11: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let rounds = single_arg(
-        "rounds",
-        'r',
-        "The number of rounds to run monkey business.",
-    )
-    .value_parser(clap::value_parser!(usize));
-    let constant_reduction = single_arg(
-        "constant",
-        'c',
-        "The constant number to reduce the worry level by.",
-    )
-    .value_parser(clap::value_parser!(usize));
-    let auto_reduction =
-        flag_arg("auto", 'a', "Automatically reduces the worry level.").conflicts_with("constant");
     let problem = Problem::new(
         "day11",
         "Determins the product of the two most active monkey's throwing. Worry levels may decrease either automatically or via a constant.",
         "Path to the input file. Monkey information seperated by a blank line",
-        vec![rounds, constant_reduction, auto_reduction],
-        parse_arguments,
         parse_file,
         run,
     )
     .with_part1(
-        CommandLineArguments { worry_level_reducation_strategy: WorryLevelReductionStrategy::Constant(3), rounds: 20 },
+        CommandLineArguments { worry_level_reducation_strategy: WorryLevelReductionArgs { constant: Some(3), auto: false }, rounds: 20 },
         "Does 20 iterations of monkey business with a constant reduction of 3.",
     )
-    .with_part2(CommandLineArguments { worry_level_reducation_strategy: WorryLevelReductionStrategy::Auto, rounds: 10_000 }, "Does 10000 iterations of Monkey Business with automatic reduction.");
+    .with_part2(CommandLineArguments { worry_level_reducation_strategy: WorryLevelReductionArgs { constant: None, auto: true }, rounds: 10_000 }, "Does 10000 iterations of Monkey Business with automatic reduction.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    worry_level_reducation_strategy: WorryLevelReductionStrategy,
+    #[command(flatten)]
+    worry_level_reducation_strategy: WorryLevelReductionArgs,
+    #[arg(short, long, help = "The number of rounds to run monkey business.")]
     rounds: usize,
 }
 
@@ -56,7 +40,7 @@ pub enum WorryLevelReductionStrategy {
     Auto,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Monkey {
     items: Vec<usize>,
     operation_operator: Operation,
@@ -67,34 +51,48 @@ pub struct Monkey {
     inspect_count: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operation {
     Add,
     Multiply,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operand {
     Value(usize),
     Old,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    let constant_reduction = args
-        .get_one::<usize>("constant")
-        .map(|constant| WorryLevelReductionStrategy::Constant(*constant));
-    let auto_reduction = args.get_one::<bool>("auto").and_then(|value| match value {
-        true => Some(WorryLevelReductionStrategy::Auto),
-        false => None,
-    });
-    let reduction_strategy = match (constant_reduction, auto_reduction) {
-        (Some(constant), None) => constant,
-        (None, Some(auto)) => auto,
-        _ => unreachable!(),
-    };
-    CommandLineArguments {
-        worry_level_reducation_strategy: reduction_strategy,
-        rounds: *args.get_one::<usize>("rounds").expect("Valid arguments"),
+#[derive(Debug, Clone, Args)]
+#[group(multiple = false, required = true)]
+pub struct WorryLevelReductionArgs {
+    #[arg(
+        short,
+        long,
+        help = "The constant number to reduce the worry level by."
+    )]
+    constant: Option<usize>,
+    #[arg(short, long, help = "Automatically reduces the worry level.")]
+    auto: bool,
+}
+
+impl TryFrom<WorryLevelReductionArgs> for WorryLevelReductionStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from(args: WorryLevelReductionArgs) -> Result<Self> {
+        match args {
+            WorryLevelReductionArgs {
+                constant: Some(constant),
+                auto: false,
+            } => Ok(WorryLevelReductionStrategy::Constant(constant)),
+            WorryLevelReductionArgs {
+                constant: None,
+                auto: true,
+            } => Ok(WorryLevelReductionStrategy::Auto),
+            _ => Err(anyhow!(
+                "Exactly one worry level reduction strategy is required"
+            )),
+        }
     }
 }
 
@@ -105,7 +103,25 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_between_blank_lines(parse_monkey()).then_ignore(end())
+    parse_between_blank_lines(parse_monkey())
+        .then_ignore(end())
+        .try_map(|monkeys, span| {
+            if monkeys.iter().any(|monkey| monkey.test_div == 0) {
+                Err(Simple::custom(
+                    span,
+                    "Monkeys cannot test divisibility by 0",
+                ))
+            } else if monkeys.iter().any(|monkey| {
+                monkey.test_true >= monkeys.len() || monkey.test_false >= monkeys.len()
+            }) {
+                Err(Simple::custom(
+                    span,
+                    "Monkeys can only throw to known monkeys",
+                ))
+            } else {
+                Ok(monkeys)
+            }
+        })
 }
 
 fn parse_monkey() -> impl Parser<char, Monkey, Error = Simple<char>> {
@@ -159,8 +175,16 @@ fn parse_test() -> impl Parser<char, (usize, usize, usize), Error = Simple<char>
         .map(|((s, t), f)| (s, t, f))
 }
 
-fn run(mut input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    let safe_mod: usize = input.iter().map(|monkey| monkey.test_div).product();
+fn run(mut input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    let safe_mod = input
+        .iter()
+        .try_fold(1usize, |acc, monkey| acc.checked_mul(monkey.test_div))
+        .ok_or_else(|| anyhow!("The product of the divisibility tests overflows"))?;
+    let worry_level_reducation_strategy =
+        WorryLevelReductionStrategy::try_from(arguments.worry_level_reducation_strategy)?;
+    if let WorryLevelReductionStrategy::Constant(0) = worry_level_reducation_strategy {
+        return Err(anyhow!("Worry levels cannot be reduced by dividing by 0"));
+    }
     for _ in 0..arguments.rounds {
         for index in 0..input.len() {
             let monkey = input.get_mut(index).expect("Valid index");
@@ -171,24 +195,25 @@ fn run(mut input: ParseOutput, arguments: CommandLineArguments) -> usize {
                 .map(|item| {
                     let worry_level = match (&monkey.operation_operator, &monkey.operation_operand)
                     {
-                        (Operation::Add, Operand::Value(value)) => item + value,
-                        (Operation::Add, Operand::Old) => item + item,
-                        (Operation::Multiply, Operand::Value(value)) => item * value,
-                        (Operation::Multiply, Operand::Old) => item * item,
-                    };
+                        (Operation::Add, Operand::Value(value)) => item.checked_add(*value),
+                        (Operation::Add, Operand::Old) => item.checked_add(*item),
+                        (Operation::Multiply, Operand::Value(value)) => item.checked_mul(*value),
+                        (Operation::Multiply, Operand::Old) => item.checked_mul(*item),
+                    }
+                    .ok_or_else(|| anyhow!("Worry level {} overflows", item))?;
 
-                    let reduced_worry_level = match arguments.worry_level_reducation_strategy {
+                    let reduced_worry_level = match worry_level_reducation_strategy {
                         WorryLevelReductionStrategy::Constant(value) => worry_level / value,
                         WorryLevelReductionStrategy::Auto => worry_level % safe_mod,
                     };
 
                     if reduced_worry_level % &monkey.test_div == 0 {
-                        (reduced_worry_level, monkey.test_true)
+                        Ok((reduced_worry_level, monkey.test_true))
                     } else {
-                        (reduced_worry_level, monkey.test_false)
+                        Ok((reduced_worry_level, monkey.test_false))
                     }
                 })
-                .collect::<Vec<_>>();
+                .collect::<Result<Vec<_>>>()?;
             monkey.items.clear();
             monkey_throws
                 .into_iter()
@@ -208,9 +233,36 @@ fn run(mut input: ParseOutput, arguments: CommandLineArguments) -> usize {
         .collect::<Vec<_>>();
     inspections.sort();
     inspections.reverse();
-    inspections
+    Ok(inspections
         .into_iter()
         .take(2)
         .reduce(|acc, i| acc * i)
-        .unwrap_or(0)
+        .unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_operation_reads_operands() {
+        assert_eq!(
+            parse_operation().parse("  Operation: new = old * 19\n"),
+            Ok((Operation::Multiply, Operand::Value(19)))
+        );
+        assert_eq!(
+            parse_operation().parse("  Operation: new = old + old\n"),
+            Ok((Operation::Add, Operand::Old))
+        );
+    }
+
+    #[test]
+    fn parse_test_reads_targets() {
+        assert_eq!(
+            parse_test().parse(
+                "  Test: divisible by 23\n    If true: throw to monkey 2\n    If false: throw to monkey 3"
+            ),
+            Ok((23, 2, 3))
+        );
+    }
 }
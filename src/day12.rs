@@ -1,30 +1,20 @@
-use adventofcode2022::{flag_arg, parse_lines, BoundedPoint, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{bfs, parse_grid, BoundedPoint, Command, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just, one_of},
     Parser,
 };
-use clap::ArgMatches;
-use std::{
-    cell::LazyCell,
-    collections::{BTreeSet, VecDeque},
-};
+use clap::Args;
+use std::cell::LazyCell;
 
 type ParseOutput = Vec<Vec<MountainTile>>;
 
 pub const DAY_12: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let expand = flag_arg(
-        "expand",
-        'e',
-        "Expands the possible start positions to include 'a'",
-    );
     let problem = Problem::new(
         "day12",
         "Finds the shortest path to the end goal on the mountain.",
         "Path to the input file. File should consist of all lower case letters and one S and E.",
-        vec![expand],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -39,12 +29,17 @@ pub const DAY_12: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(
+        short,
+        long,
+        help = "Expands the possible start positions to include 'a'"
+    )]
     expand: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MountainTile {
     Base(char),
     Start,
@@ -61,12 +56,6 @@ impl MountainTile {
     }
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        expand: *args.get_one::<bool>("expand").expect("Valid arguments"),
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -74,7 +63,24 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_mountain_tile().repeated().at_least(1)).then_ignore(end())
+    parse_grid(parse_mountain_tile())
+        .then_ignore(end())
+        .try_map(|mountain, span| {
+            let count = |target: MountainTile| {
+                mountain
+                    .iter()
+                    .flatten()
+                    .filter(|tile| **tile == target)
+                    .count()
+            };
+            match (count(MountainTile::Start), count(MountainTile::End)) {
+                (1, 1) => Ok(mountain),
+                _ => Err(Simple::custom(
+                    span,
+                    "The map must have exactly one start and one end",
+                )),
+            }
+        })
 }
 
 fn parse_mountain_tile() -> impl Parser<char, MountainTile, Error = Simple<char>> {
@@ -98,12 +104,7 @@ struct Node {
     adjacents: Vec<usize>,
 }
 
-#[derive(Debug, Clone)]
-struct Path {
-    nodes: Vec<usize>,
-}
-
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
     let graph = build_graph(input);
 
     let start = graph
@@ -116,71 +117,27 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
             _ => false,
         })
         .map(|(index, _)| index)
-        .collect();
-
-    shortest_path(start, &graph)
-        .expect("At least one path")
-        .nodes
-        .len()
-        - 1
-}
-
-fn shortest_path(start_items: Vec<usize>, graph: &Graph) -> Option<Path> {
-    let mut visited = start_items.iter().cloned().collect::<BTreeSet<usize>>();
-
-    let mut queue = start_items
-        .into_iter()
-        .map(|node| Path { nodes: vec![node] })
-        .collect::<VecDeque<Path>>();
-
-    let mut final_path: Option<Path> = None;
-
-    while queue.len() > 0 {
-        let current = queue.pop_front().expect("At least one item in the queue");
-
-        match &final_path {
-            Some(current_best) => {
-                if current_best.nodes.len() < current.nodes.len() {
-                    continue;
-                }
-            }
-            None => (),
-        }
-
-        let last = current.nodes.last().expect("At least one item in the path");
-        let filtered_adjacents = graph
-            .nodes
-            .get(*last)
-            .expect("Valid index")
-            .adjacents
-            .iter()
-            .filter(|node| !visited.contains(node))
-            .cloned()
-            .collect::<Vec<_>>();
-
-        filtered_adjacents
-            .into_iter()
-            .map(|index| (index, graph.nodes.get(index).expect("Valid index")))
-            .for_each(|(index, node)| {
-                let mut new = current.clone();
-                new.nodes.push(index);
-                match node.value {
-                    MountainTile::End => match &final_path {
-                        Some(current_best) => {
-                            if current_best.nodes.len() > new.nodes.len() {
-                                final_path = Some(new)
-                            }
-                        }
-                        None => final_path = Some(new),
-                    },
-                    _ => {
-                        visited.insert(index);
-                        queue.push_back(new);
-                    }
-                }
-            });
-    }
-    final_path
+        .collect::<Vec<_>>();
+
+    bfs(
+        start,
+        |index| {
+            graph
+                .nodes
+                .get(*index)
+                .expect("Valid index")
+                .adjacents
+                .clone()
+        },
+        |index| {
+            matches!(
+                graph.nodes.get(*index).expect("Valid index").value,
+                MountainTile::End
+            )
+        },
+    )
+    .map(|result| result.cost)
+    .ok_or_else(|| anyhow!("No path reaches the end"))
 }
 
 fn build_graph(mountain: Vec<Vec<MountainTile>>) -> Graph {
@@ -230,3 +187,19 @@ fn find_adjacent_nodes(node: &mut Node, mountain: &Vec<Vec<MountainTile>>) {
 fn point_to_node(point: &BoundedPoint) -> usize {
     point.y * (point.max_x + 1) + point.x
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mountain_tile_reads_each_tile() {
+        assert_eq!(
+            parse_mountain_tile().parse("q"),
+            Ok(MountainTile::Base('q'))
+        );
+        assert_eq!(parse_mountain_tile().parse("S"), Ok(MountainTile::Start));
+        assert_eq!(parse_mountain_tile().parse("E"), Ok(MountainTile::End));
+        assert!(parse_mountain_tile().parse("A").is_err());
+    }
+}
@@ -1,6 +1,4 @@
-use adventofcode2022::{
-    parse_between_blank_lines, parse_usize, single_arg, Command, ParseError, Problem,
-};
+use adventofcode2022::{parse_between_blank_lines, parse_usize, Command, ParseError, Problem};
 use anyhow::Result;
 use chumsky::{
     prelude::Simple,
@@ -8,20 +6,16 @@ use chumsky::{
     recursive::recursive,
     text, Parser,
 };
-use clap::{ArgMatches, ValueEnum};
+use clap::{Args, ValueEnum};
 use std::{cell::LazyCell, iter::once};
 
 type ParseOutput = Vec<(Signal, Signal)>;
 
 pub const DAY_13: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let ordering = single_arg("ordering", 'o', "The ordering strategy to use.")
-        .value_parser(clap::value_parser!(PacketOrder));
     let problem = Problem::new(
         "day13",
         "Determines order properties of packets",
         "Path to the input file. Groups of two packets, one packet on each line. Each group is seperated by a newline.",
-        vec![ordering],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -36,20 +30,12 @@ pub enum PacketOrder {
     Global,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The ordering strategy to use.")]
     ordering: PacketOrder,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        ordering: args
-            .get_one::<PacketOrder>("ordering")
-            .expect("Valid arguments")
-            .clone(),
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Signal {
     Literal(usize),
@@ -167,3 +153,20 @@ fn compare_signals(left: &Signal, right: &Signal) -> Option<bool> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_signal_reads_nested_lists() {
+        assert_eq!(
+            parse_signal().parse("[[1],4]"),
+            Ok(Signal::List(vec![
+                Signal::List(vec![Signal::Literal(1)]),
+                Signal::Literal(4)
+            ]))
+        );
+        assert_eq!(parse_signal().parse("[]"), Ok(Signal::List(vec![])));
+    }
+}
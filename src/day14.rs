@@ -1,51 +1,51 @@
-use adventofcode2022::{flag_arg, parse_lines, parse_usize, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{
+    parse_isize, parse_lines, Command, Grid, ParseError, Problem, RenderArgs, RenderCell, Viewport,
+};
+use anyhow::{anyhow, Result};
+use ariadne::Color;
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use itertools::Itertools;
 use std::{
     cell::LazyCell,
     cmp::{max, min},
     collections::{HashSet, VecDeque},
+    iter::once,
 };
 
 type ParseOutput = Vec<Vec<Line>>;
 
+const MAX_COORDINATE: isize = 1_000;
+
 pub const DAY_14: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let bottom = flag_arg("bottom", 'b', "There is a bottom in the cave");
     let problem = Problem::new(
         "day14",
         "Find when sand reaches steady state.",
         "Path to the input file. Each line is a rock vien in the cave. Rock veins are continuous horizontal and vertical lines.",
-        vec![bottom],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { bottom: false }, "Finds how much sand needs to fall before it falls into the abyss.")
-    .with_part2(CommandLineArguments { bottom: true }, "Finds how much sand needs to fall before no more can fit");
+    .with_part1(CommandLineArguments { bottom: false, render: RenderArgs::default() }, "Finds how much sand needs to fall before it falls into the abyss.")
+    .with_part2(CommandLineArguments { bottom: true, render: RenderArgs::default() }, "Finds how much sand needs to fall before no more can fit");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "There is a bottom in the cave")]
     bottom: bool,
-}
-
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        bottom: *args.get_one::<bool>("bottom").expect("Valid arguments"),
-    }
+    #[command(flatten)]
+    render: RenderArgs,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Point {
-    x: usize,
-    y: usize,
+    x: isize,
+    y: isize,
 }
 
 impl Point {
@@ -67,12 +67,73 @@ impl Point {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Line {
     start: Point,
     end: Point,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaveTile {
+    Air,
+    Rock,
+    Sand,
+    Source,
+}
+
+impl RenderCell for CaveTile {
+    fn to_char(&self) -> char {
+        match self {
+            CaveTile::Air => '.',
+            CaveTile::Rock => '#',
+            CaveTile::Sand => 'o',
+            CaveTile::Source => '+',
+        }
+    }
+
+    fn color(&self) -> Option<Color> {
+        match self {
+            CaveTile::Air => None,
+            CaveTile::Rock => Some(Color::Fixed(8)),
+            CaveTile::Sand => Some(Color::Yellow),
+            CaveTile::Source => Some(Color::Red),
+        }
+    }
+}
+
+struct Cave<'a> {
+    rocks: &'a HashSet<Point>,
+    occupied: &'a HashSet<Point>,
+    origin: &'a Point,
+}
+
+impl Grid for Cave<'_> {
+    type Cell = CaveTile;
+
+    fn viewport(&self) -> Viewport {
+        Viewport::from_points(
+            self.occupied
+                .iter()
+                .chain(once(self.origin))
+                .map(|point| (point.x, point.y)),
+        )
+        .expect("The origin exists")
+    }
+
+    fn cell(&self, x: isize, y: isize) -> CaveTile {
+        let point = Point { x, y };
+        if self.rocks.contains(&point) {
+            CaveTile::Rock
+        } else if self.occupied.contains(&point) {
+            CaveTile::Sand
+        } else if &point == self.origin {
+            CaveTile::Source
+        } else {
+            CaveTile::Air
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 enum Direction {
     Down,
@@ -81,19 +142,19 @@ enum Direction {
 }
 
 impl Line {
-    fn get_min_y(&self) -> usize {
+    fn get_min_y(&self) -> isize {
         min(self.start.y, self.end.y)
     }
 
-    fn get_max_y(&self) -> usize {
+    fn get_max_y(&self) -> isize {
         max(self.start.y, self.end.y)
     }
 
-    fn get_min_x(&self) -> usize {
+    fn get_min_x(&self) -> isize {
         min(self.start.x, self.end.x)
     }
 
-    fn get_max_x(&self) -> usize {
+    fn get_max_x(&self) -> isize {
         max(self.start.x, self.end.x)
     }
 
@@ -136,17 +197,32 @@ fn parse_line_segments() -> impl Parser<char, Vec<Line>, Error = Simple<char>> {
 }
 
 fn parse_point() -> impl Parser<char, Point, Error = Simple<char>> {
-    parse_usize()
+    parse_isize()
         .then_ignore(just(","))
-        .then(parse_usize())
+        .then(parse_isize())
         .map(|(x, y)| Point { x, y })
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    let render = arguments.render.options();
     let mut lines = input
         .into_iter()
         .flat_map(|lines| lines.into_iter())
         .collect::<Vec<Line>>();
+    if let Some(point) = lines
+        .iter()
+        .flat_map(|line| [&line.start, &line.end])
+        .find(|point| {
+            !(0..=MAX_COORDINATE).contains(&point.x) || !(0..=MAX_COORDINATE).contains(&point.y)
+        })
+    {
+        return Err(anyhow!(
+            "Rock at {},{} is outside the cave, coordinates must be between 0 and {}",
+            point.x,
+            point.y,
+            MAX_COORDINATE
+        ));
+    }
     let mut max_y = lines.iter().map(|line| line.get_max_y()).max().unwrap_or(0);
 
     if arguments.bottom {
@@ -164,7 +240,7 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
     }
 
     let mut count = 0usize;
-    let mut occupied_points: HashSet<Point> =
+    let rocks: HashSet<Point> =
         lines
             .into_iter()
             .map(|line| line.to_points())
@@ -172,6 +248,7 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
                 acc.extend(value.into_iter());
                 acc
             });
+    let mut occupied_points = rocks.clone();
     let origin = Point { x: 500, y: 0 };
     let mut path = VecDeque::from([origin.clone()]);
     let valid_directions = vec![Direction::Down, Direction::DownLeft, Direction::DownRight];
@@ -190,6 +267,16 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
                 None => {
                     occupied_points.insert(current_point.clone());
                     count += 1;
+                    if let Some(render) = &render {
+                        if render.is_render_step(count) {
+                            let cave = Cave {
+                                rocks: &rocks,
+                                occupied: &occupied_points,
+                                origin: &origin,
+                            };
+                            render.print(&format!("After {} grains of sand", count), &cave);
+                        }
+                    }
                     break;
                 }
             }
@@ -199,7 +286,20 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
             break;
         }
     }
-    count
+
+    if let Some(render) = &render {
+        if !render.is_render_step(count) {
+            let cave = Cave {
+                rocks: &rocks,
+                occupied: &occupied_points,
+                origin: &origin,
+            };
+            render.print(&format!("After {} grains of sand", count), &cave);
+        }
+        render.finish()?;
+    }
+
+    Ok(count)
 }
 
 fn is_valid_next_tile(
@@ -215,3 +315,32 @@ fn is_valid_next_tile(
         Some(next_point)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_point_reads_coordinates() {
+        assert_eq!(parse_point().parse("498,4"), Ok(Point { x: 498, y: 4 }));
+    }
+
+    #[test]
+    fn parse_line_segments_requires_two_points() {
+        assert!(parse_line_segments()
+            .then_ignore(end())
+            .parse("498,4")
+            .is_err());
+    }
+
+    #[test]
+    fn run_rejects_rocks_outside_the_cave() {
+        let input = parse_file("498,4 -> 498,1001\n".to_string()).expect("Input parses");
+        let arguments = CommandLineArguments {
+            bottom: false,
+            render: RenderArgs::default(),
+        };
+
+        assert!(run(input, arguments).is_err());
+    }
+}
@@ -1,47 +1,31 @@
 use adventofcode2022::{
-    absolute_difference, parse_isize, parse_lines, single_arg, Command, ParseError, Problem,
+    absolute_difference, parse_isize, parse_lines, Command, Interval, IntervalSet, ParseError,
+    Problem,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use itertools::Itertools;
-use std::{
-    cell::LazyCell,
-    cmp::{max, min},
-    collections::VecDeque,
-};
+use std::cell::LazyCell;
 
 type ParseOutput = Vec<Sensor>;
 
+const MAX_COORDINATE: isize = 1_000_000_000_000;
+
 pub const DAY_15: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let line = single_arg(
-        "line",
-        'l',
-        "The line to scan and get the number of impossible spaces",
-    )
-    .value_parser(clap::value_parser!(isize));
-    let area = single_arg(
-        "area",
-        'a',
-        "The max area to look for an open space. Returns the tuning frequency of the space",
-    )
-    .value_parser(clap::value_parser!(isize))
-    .conflicts_with("line");
     let problem = Problem::new(
         "day15",
         "Finds statistics about the results of our sensor's beacon targets.",
         "Path to the input file. Each line should have a sensor, it's postition, and the position of the beacon that it's closest too.",
-        vec![line, area],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { scanner_mode: ScannerMode::ScanLine(2_000_000) }, "Finds the number of positions where the signal can not exist for y = 2_000_000")
-    .with_part2(CommandLineArguments { scanner_mode: ScannerMode::ScanArea(4_000_000) }, "Finds the tuning frequency for the area of 4_000_000");
+    .with_part1(CommandLineArguments { scanner_mode: ScannerModeArgs { line: Some(2_000_000), area: None } }, "Finds the number of positions where the signal can not exist for y = 2_000_000")
+    .with_part2(CommandLineArguments { scanner_mode: ScannerModeArgs { line: None, area: Some(4_000_000) } }, "Finds the tuning frequency for the area of 4_000_000");
     Box::new(problem)
 });
 
@@ -51,24 +35,41 @@ pub enum ScannerMode {
     ScanArea(isize),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    scanner_mode: ScannerMode,
+    #[command(flatten)]
+    scanner_mode: ScannerModeArgs,
+}
+
+#[derive(Debug, Clone, Args)]
+#[group(multiple = false, required = true)]
+pub struct ScannerModeArgs {
+    #[arg(
+        short,
+        long,
+        help = "The line to scan and get the number of impossible spaces"
+    )]
+    line: Option<isize>,
+    #[arg(
+        short,
+        long,
+        help = "The max area to look for an open space. Returns the tuning frequency of the space"
+    )]
+    area: Option<isize>,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    let line = args
-        .get_one::<isize>("line")
-        .map(|constant| ScannerMode::ScanLine(*constant));
-    let area = args
-        .get_one::<isize>("area")
-        .map(|constant| ScannerMode::ScanArea(*constant));
-    let scanner_mode = match (line, area) {
-        (Some(y), None) => y,
-        (None, Some(y)) => y,
-        _ => unreachable!(),
-    };
-    CommandLineArguments { scanner_mode }
+impl TryFrom<ScannerModeArgs> for ScannerMode {
+    type Error = anyhow::Error;
+
+    fn try_from(args: ScannerModeArgs) -> Result<Self> {
+        match (args.line, args.area) {
+            (Some(y), None) => Ok(ScannerMode::ScanLine(y)),
+            (None, Some(search)) => Ok(ScannerMode::ScanArea(search)),
+            _ => Err(anyhow!(
+                "Exactly one of a line or an area to scan is required"
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -77,24 +78,12 @@ pub struct Point {
     y: isize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Sensor {
     location: Point,
     beacon: Point,
 }
 
-#[derive(Debug, Clone)]
-struct Range {
-    start: isize,
-    end: isize,
-}
-
-impl Range {
-    fn overlap_with_other(&self, other: &Range) -> isize {
-        min(self.end, other.end) - max(self.start, other.start) + 1
-    }
-}
-
 impl Sensor {
     fn get_beacon_distance(&self) -> isize {
         self.get_distance(&self.beacon)
@@ -105,7 +94,7 @@ impl Sensor {
             + absolute_difference(self.location.y, point.y)
     }
 
-    fn get_impossible_points_for_y(&self, target_y: isize) -> Option<Range> {
+    fn get_impossible_points_for_y(&self, target_y: isize) -> Option<Interval<isize>> {
         let max_distance = self.get_beacon_distance();
         let target_distance = self.get_distance(&Point {
             x: self.location.x,
@@ -113,10 +102,10 @@ impl Sensor {
         });
 
         if target_distance <= max_distance {
-            Some(Range {
-                start: self.location.x - (max_distance - target_distance),
-                end: self.location.x + (max_distance - target_distance),
-            })
+            Some(Interval::new(
+                self.location.x - (max_distance - target_distance),
+                self.location.x + (max_distance - target_distance),
+            ))
         } else {
             None
         }
@@ -149,58 +138,247 @@ fn parse_point() -> impl Parser<char, Point, Error = Simple<char>> {
         .map(|(x, y)| Point { x, y })
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
-    match arguments.scanner_mode {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<isize> {
+    let in_range = |coordinate: isize| (-MAX_COORDINATE..=MAX_COORDINATE).contains(&coordinate);
+    if let Some(point) = input
+        .iter()
+        .flat_map(|sensor| [&sensor.location, &sensor.beacon])
+        .find(|point| !in_range(point.x) || !in_range(point.y))
+    {
+        return Err(anyhow!(
+            "Point {},{} is too far away, coordinates must be between -{} and {}",
+            point.x,
+            point.y,
+            MAX_COORDINATE,
+            MAX_COORDINATE
+        ));
+    }
+
+    match ScannerMode::try_from(arguments.scanner_mode)? {
+        ScannerMode::ScanLine(y) if !in_range(y) => Err(anyhow!(
+            "Line {} is too far away, it must be between -{} and {}",
+            y,
+            MAX_COORDINATE,
+            MAX_COORDINATE
+        )),
+        ScannerMode::ScanArea(search) if !in_range(search) => Err(anyhow!(
+            "Area {} is too large, it must be at most {}",
+            search,
+            MAX_COORDINATE
+        )),
         ScannerMode::ScanLine(y) => {
-            let (_, ranges) = find_ranges_for_y(&input, y);
-            ranges
-                .into_iter()
-                .map(|range| range.end - range.start)
-                .sum()
+            let coverage = find_coverage_for_y(&input, y);
+            let beacons = input
+                .iter()
+                .filter(|sensor| sensor.beacon.y == y && coverage.contains(&sensor.beacon.x))
+                .map(|sensor| sensor.beacon.x)
+                .unique()
+                .count() as isize;
+            coverage
+                .length()
+                .map(|length| length - beacons)
+                .ok_or_else(|| anyhow!("Line {} has too many covered positions to count", y))
         }
         ScannerMode::ScanArea(search) => {
+            let area = Interval::new(0, search);
             let mut y = 0;
             while y <= search {
-                let (min_overlap, ranges) = find_ranges_for_y(&input, y);
-                if ranges.len() == 2 {
-                    return (ranges.get(0).expect("At least 1").end + 1) * 4_000_000 + y;
+                match covered_row_slack(&input, y, &area) {
+                    Some(slack) => y += slack + 1,
+                    None => {
+                        return IntervalSet::from(area)
+                            .subtract(&find_coverage_for_y(&input, y))
+                            .iter()
+                            .next()
+                            .map(|gap| gap.start * 4_000_000 + y)
+                            .ok_or_else(|| anyhow!("Row {} has no gap", y));
+                    }
                 }
+            }
+            Err(anyhow!("Every point in the search area is covered"))
+        }
+    }
+}
 
-                y += match min_overlap {
-                    Some(overlap) => max(1, overlap / 2),
-                    None => 1,
-                }
+// Each end of a sensor's coverage moves at most one column per row, so a row
+// that is covered by a chain of overlapping intervals stays covered until one
+// of the overlaps or the margins at the edges of the area runs out. Returns
+// how many of the following rows are certainly covered, or None on a gap.
+fn covered_row_slack(sensors: &[Sensor], y: isize, area: &Interval<isize>) -> Option<isize> {
+    let intervals = sensors
+        .iter()
+        .filter_map(|sensor| sensor.get_impossible_points_for_y(y))
+        .sorted()
+        .collect::<Vec<_>>();
+    let mut covered = area.start - 1;
+    let mut slack = isize::MAX;
+    let mut index = 0;
+
+    while covered < area.end {
+        let mut next: Option<Interval<isize>> = None;
+        while let Some(interval) = intervals
+            .get(index)
+            .filter(|interval| interval.start <= covered + 1)
+        {
+            if next.is_none_or(|next| interval.end > next.end) {
+                next = Some(*interval);
             }
-            unreachable!()
+            index += 1;
         }
+
+        let next = next.filter(|next| next.end > covered)?;
+        let overlap = covered + 1 - next.start;
+        slack = slack.min(if covered < area.start {
+            overlap
+        } else {
+            overlap / 2
+        });
+        covered = next.end;
     }
+
+    Some(slack.min(covered - area.end))
 }
 
-fn find_ranges_for_y(sensors: &Vec<Sensor>, y: isize) -> (Option<isize>, VecDeque<Range>) {
+fn find_coverage_for_y(sensors: &[Sensor], y: isize) -> IntervalSet<isize> {
     sensors
         .iter()
         .filter_map(|sensor| sensor.get_impossible_points_for_y(y))
-        .sorted_by(|a, b| a.start.cmp(&b.start))
-        .fold(
-            (None, VecDeque::<Range>::new()),
-            |(min_overlap, mut acc), range| match acc.back_mut() {
-                Some(last_range) => {
-                    let overlap = last_range.overlap_with_other(&range);
-                    if overlap >= 0 {
-                        last_range.end = max(last_range.end, range.end);
-                    } else {
-                        acc.push_back(range);
-                    };
-                    let new_overlap = match min_overlap {
-                        Some(old_overlap) => Some(min(old_overlap, overlap)),
-                        None => Some(overlap),
-                    };
-                    (new_overlap, acc)
-                }
-                None => {
-                    acc.push_back(range);
-                    (None, acc)
-                }
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{property, Rng};
+
+    const SAMPLE: &str = include_str!("../day15/sample.txt");
+
+    fn generate_input(rng: &mut Rng, size: usize) -> ParseOutput {
+        let size = size as isize;
+        let reach = (size / 3).max(1);
+        (0..rng.usize_in(1..=size as usize))
+            .map(|_| {
+                let location = Point {
+                    x: rng.isize_in(-size..=size * 2),
+                    y: rng.isize_in(-size..=size * 2),
+                };
+                let beacon = Point {
+                    x: location.x + rng.isize_in(-reach..=reach),
+                    y: location.y + rng.isize_in(-reach..=reach),
+                };
+                Sensor { location, beacon }
+            })
+            .collect()
+    }
+
+    fn is_covered(sensors: &[Sensor], x: isize, y: isize) -> bool {
+        sensors.iter().any(|sensor| {
+            let radius = (sensor.location.x - sensor.beacon.x).abs()
+                + (sensor.location.y - sensor.beacon.y).abs();
+            (sensor.location.x - x).abs() + (sensor.location.y - y).abs() <= radius
+        })
+    }
+
+    fn reference_scan_line(sensors: &[Sensor], y: isize) -> isize {
+        let min_x = sensors
+            .iter()
+            .map(|sensor| sensor.location.x)
+            .min()
+            .unwrap_or(0);
+        let max_x = sensors
+            .iter()
+            .map(|sensor| sensor.location.x)
+            .max()
+            .unwrap_or(0);
+        let reach = sensors
+            .iter()
+            .map(|sensor| {
+                (sensor.location.x - sensor.beacon.x).abs()
+                    + (sensor.location.y - sensor.beacon.y).abs()
+            })
+            .max()
+            .unwrap_or(0);
+
+        (min_x - reach..=max_x + reach)
+            .filter(|x| is_covered(sensors, *x, y))
+            .filter(|x| {
+                !sensors
+                    .iter()
+                    .any(|sensor| sensor.beacon.x == *x && sensor.beacon.y == y)
+            })
+            .count() as isize
+    }
+
+    fn reference_scan_area(sensors: &[Sensor], search: isize) -> Option<isize> {
+        (0..=search)
+            .flat_map(|y| (0..=search).map(move |x| (x, y)))
+            .find(|(x, y)| !is_covered(sensors, *x, *y))
+            .map(|(x, y)| x * 4_000_000 + y)
+    }
+
+    #[test]
+    fn parse_point_reads_negative_coordinates() {
+        assert_eq!(
+            parse_point().parse("x=-2, y=15"),
+            Ok(Point { x: -2, y: 15 })
+        );
+    }
+
+    #[test]
+    fn scan_line_matches_reference() {
+        property(100, 30, |rng, size| {
+            let input = generate_input(rng, size);
+            let y = rng.isize_in(0..=size as isize);
+            let arguments = CommandLineArguments {
+                scanner_mode: ScannerModeArgs {
+                    line: Some(y),
+                    area: None,
+                },
+            };
+
+            assert_eq!(
+                run(input.clone(), arguments).ok(),
+                Some(reference_scan_line(&input, y))
+            );
+        });
+    }
+
+    #[test]
+    fn scan_area_matches_reference() {
+        property(100, 30, |rng, size| {
+            let input = generate_input(rng, size);
+            let search = size as isize;
+            let arguments = CommandLineArguments {
+                scanner_mode: ScannerModeArgs {
+                    line: None,
+                    area: Some(search),
+                },
+            };
+
+            assert_eq!(
+                run(input.clone(), arguments).ok(),
+                reference_scan_area(&input, search)
+            );
+        });
+    }
+
+    #[test]
+    fn far_away_points_are_an_error() {
+        let line = |y| CommandLineArguments {
+            scanner_mode: ScannerModeArgs {
+                line: Some(y),
+                area: None,
             },
+        };
+        let far = parse_file(
+            "Sensor at x=9000000000000000000, y=0: closest beacon is at x=-9000000000000000000, y=0"
+                .to_string(),
         )
+        .unwrap();
+        let sample = parse_file(SAMPLE.to_string()).unwrap();
+
+        assert!(run(far, line(0)).is_err());
+        assert!(run(sample.clone(), line(isize::MAX)).is_err());
+        assert_eq!(run(sample, line(10)).unwrap(), 26);
+    }
 }
@@ -1,36 +1,27 @@
-use adventofcode2022::{parse_lines, parse_usize, single_arg, Command, ParseError, Problem};
+use adventofcode2022::{bfs, parse_lines, parse_usize, Command, ParseError, Problem};
 use anyhow::Result;
 use chumsky::{
     prelude::Simple,
-    primitive::{end, just},
-    text, Parser,
+    primitive::{end, filter, just},
+    Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use itertools::Itertools;
 use std::{
     cell::LazyCell,
     cmp::min,
-    collections::{BTreeSet, HashMap, VecDeque},
+    collections::{BTreeSet, HashMap},
     iter::once,
 };
+use tracing::debug;
 
 type ParseOutput = Vec<Valve>;
 
 pub const DAY_16: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let time = single_arg("time", 't', "The time to release the pressure.")
-        .value_parser(clap::value_parser!(u16));
-    let entities = single_arg(
-        "entities",
-        'e',
-        "The number of entities who can open valves.",
-    )
-    .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day16",
         "Finds the maximum amount of pressure you can release in the given time peroid.",
         "Path to the input file. Each line describes a cave. A cave has a name, pressure rate, and the caves it's connected to.",
-        vec![time, entities],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -39,19 +30,14 @@ pub const DAY_16: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The time to release the pressure.")]
     time: u16,
+    #[arg(short, long, help = "The number of entities who can open valves.")]
     entities: usize,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        time: *args.get_one::<u16>("time").expect("Valid arguments"),
-        entities: *args.get_one::<usize>("entities").expect("Valid arguments"),
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -66,25 +52,53 @@ pub struct Valve {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_valve()).then_ignore(end())
+    parse_lines(parse_valve())
+        .then_ignore(end())
+        .try_map(|valves, span| {
+            let names = valves
+                .iter()
+                .map(|valve| valve.name)
+                .collect::<BTreeSet<_>>();
+            if !names.contains(&ValveName::new("AA".to_string())) {
+                return Err(Simple::custom(span, "There must be a valve named AA"));
+            }
+
+            match valves
+                .iter()
+                .flat_map(|valve| valve.connections.iter())
+                .find(|connection| !names.contains(connection))
+            {
+                Some(_) => Err(Simple::custom(
+                    span,
+                    "Every tunnel must lead to a known valve",
+                )),
+                None => Ok(valves),
+            }
+        })
 }
 
 fn parse_valve() -> impl Parser<char, Valve, Error = Simple<char>> {
     just("Valve ")
         .ignore_then(parse_valve_name())
         .then_ignore(just(" has flow rate="))
-        .then(parse_usize())
+        .then(parse_usize().try_map(|rate, span| {
+            u16::try_from(rate).map_err(|e| Simple::custom(span, e.to_string()))
+        }))
         .then_ignore(just("; tunnels lead to valves ").or(just("; tunnel leads to valve ")))
         .then(parse_valve_name().separated_by(just(", ")))
         .map(|((name, rate), connections)| Valve {
             name,
-            rate: rate as u16,
+            rate,
             connections,
         })
 }
 
 fn parse_valve_name() -> impl Parser<char, ValveName, Error = Simple<char>> {
-    text::ident().map(|name| ValveName::new(name))
+    filter(|c: &char| c.is_ascii_uppercase())
+        .repeated()
+        .exactly(2)
+        .collect::<String>()
+        .map(|name| ValveName::new(name))
 }
 
 fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
@@ -92,12 +106,8 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
         .into_iter()
         .map(|valve| (valve.name, valve))
         .collect::<HashMap<_, _>>();
-    let graph = Graph {
-        nodes: valves.clone(),
-    };
     let start = ValveName::new("AA".to_string());
-    let target_nodes = graph
-        .nodes
+    let target_nodes = valves
         .values()
         .filter(|valve| valve.rate > 0)
         .map(|valve| valve.name.clone())
@@ -111,16 +121,22 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
                 source,
                 target_nodes
                     .iter()
+                    .filter(|target| **target != source)
                     .filter_map(|target| {
-                        shortest_path(vec![source], &graph, target)
-                            .map(|path| (*target, path.nodes.len() as u16))
+                        bfs(
+                            [source],
+                            |name| valves.get(name).expect("Valid index").connections.clone(),
+                            |name| name == target,
+                        )
+                        .map(|path| (*target, path.cost as u16 + 1))
                     })
                     .collect::<HashMap<_, _>>(),
             )
         })
         .collect::<HashMap<_, _>>();
 
-    best_pressure_possible(
+    let mut memo = Memo::default();
+    let result = best_pressure_possible(
         vec![
             ValveDistance {
                 target: start,
@@ -132,8 +148,22 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
         BTreeSet::from([start]),
         &valves,
         &paths,
-        &mut HashMap::new(),
-    ) as usize
+        &mut memo,
+    );
+    debug!(
+        states = memo.states.len(),
+        hits = memo.hits,
+        hit_rate = memo.hits as f64 / (memo.hits + memo.states.len()) as f64,
+        "Valve memo"
+    );
+
+    result as usize
+}
+
+#[derive(Default)]
+struct Memo {
+    states: HashMap<(Vec<ValveDistance>, u16, BTreeSet<ValveName>), u16>,
+    hits: usize,
 }
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
@@ -162,10 +192,16 @@ fn best_pressure_possible(
     visited: BTreeSet<ValveName>,
     valves: &HashMap<ValveName, Valve>,
     paths: &HashMap<ValveName, HashMap<ValveName, u16>>,
-    cache: &mut HashMap<(Vec<ValveDistance>, u16, BTreeSet<ValveName>), u16>,
+    cache: &mut Memo,
 ) -> u16 {
-    match cache.get(&(destinations.clone(), minutes_left, visited.clone())) {
-        Some(result) => *result,
+    match cache
+        .states
+        .get(&(destinations.clone(), minutes_left, visited.clone()))
+    {
+        Some(result) => {
+            cache.hits += 1;
+            *result
+        }
         None => {
             let (arrived, enroute): (Vec<_>, Vec<_>) = destinations
                 .clone()
@@ -271,76 +307,28 @@ fn best_pressure_possible(
             } else {
                 0
             };
-            cache.insert((destinations, minutes_left, visited), result);
+            cache
+                .states
+                .insert((destinations, minutes_left, visited), result);
 
             result
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct Graph {
-    nodes: HashMap<ValveName, Valve>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[derive(Debug, Clone)]
-struct Path {
-    nodes: Vec<ValveName>,
-}
-
-fn shortest_path(start_items: Vec<ValveName>, graph: &Graph, target: &ValveName) -> Option<Path> {
-    let mut visited = start_items.iter().cloned().collect::<BTreeSet<ValveName>>();
-
-    let mut queue = start_items
-        .into_iter()
-        .map(|node| Path { nodes: vec![node] })
-        .collect::<VecDeque<Path>>();
-
-    let mut final_path: Option<Path> = None;
-
-    while queue.len() > 0 {
-        let current = queue.pop_front().expect("At least one item in the queue");
-
-        match &final_path {
-            Some(current_best) => {
-                if current_best.nodes.len() < current.nodes.len() {
-                    continue;
-                }
-            }
-            None => (),
-        }
-
-        let last = current.nodes.last().expect("At least one item in the path");
-        let filtered_adjacents = graph
-            .nodes
-            .get(last)
-            .expect("Valid index")
-            .connections
-            .iter()
-            .filter(|node| !visited.contains(node))
-            .cloned()
-            .collect::<Vec<_>>();
-
-        filtered_adjacents
-            .into_iter()
-            .map(|index| (index, graph.nodes.get(&index).expect("Valid index")))
-            .for_each(|(index, node)| {
-                let mut new = current.clone();
-                new.nodes.push(index);
-                if node.name == *target {
-                    match &final_path {
-                        Some(current_best) => {
-                            if current_best.nodes.len() > new.nodes.len() {
-                                final_path = Some(new)
-                            }
-                        }
-                        None => final_path = Some(new),
-                    }
-                } else {
-                    visited.insert(index);
-                    queue.push_back(new);
-                }
-            });
+    #[test]
+    fn parse_valve_reads_single_tunnel() {
+        assert_eq!(
+            parse_valve().parse("Valve HH has flow rate=22; tunnel leads to valve GG"),
+            Ok(Valve {
+                name: ValveName::new("HH".to_string()),
+                rate: 22,
+                connections: vec![ValveName::new("GG".to_string())],
+            })
+        );
     }
-    final_path
 }
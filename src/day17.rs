@@ -1,41 +1,40 @@
-use adventofcode2022::{single_arg, BoundedPoint, Command, ParseError, PointDirection, Problem};
+use adventofcode2022::{
+    find_cycle, BoundedPoint, Command, Grid, ParseError, PointDirection, Problem, RenderArgs,
+    RenderCell, Viewport,
+};
 use anyhow::Result;
+use ariadne::Color;
 use chumsky::{prelude::Simple, primitive::end, primitive::just, text, Parser};
-use clap::ArgMatches;
-use itertools::Itertools;
-use std::{
-    cell::LazyCell,
-    collections::{HashSet, VecDeque},
-};
+use clap::Args;
+use std::{cell::LazyCell, collections::VecDeque};
+use tracing::debug;
 
 type ParseOutput = Vec<PointDirection>;
 
 pub const DAY_17: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let number = single_arg("number", 'n', "The number of rocks that fall")
-        .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day17",
         "Finds the height of falling rocks after a number of rocks have fallen",
         "Path to the input file. The wind direction at any given iteration. Cycles to the start once input ends.",
-        vec![number],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { n: 2022 }, "Finds the height of the rock tower after 2022 iterations.")
-    .with_part2(CommandLineArguments { n: 1_000_000_000_000}, "Finds the height of the rock tower after 1_000_000_000_000 iterations.");
+    .with_part1(CommandLineArguments { n: 2022, render: RenderArgs::default() }, "Finds the height of the rock tower after 2022 iterations.")
+    .with_part2(CommandLineArguments { n: 1_000_000_000_000, render: RenderArgs::default() }, "Finds the height of the rock tower after 1_000_000_000_000 iterations.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(
+        short,
+        long = "number",
+        value_name = "NUMBER",
+        help = "The number of rocks that fall"
+    )]
     n: usize,
-}
-
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        n: *args.get_one::<usize>("number").expect("Valid arguments"),
-    }
+    #[command(flatten)]
+    render: RenderArgs,
 }
 
 fn parse_file(file: String) -> Result<ParseOutput> {
@@ -47,6 +46,7 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
     parse_direction()
         .repeated()
+        .at_least(1)
         .then_ignore(text::newline())
         .then_ignore(end())
 }
@@ -57,6 +57,11 @@ fn parse_direction() -> impl Parser<char, PointDirection, Error = Simple<char>>
     right.or(left)
 }
 
+// Two chambers with the same rock, wind and top 32 rows are assumed to keep
+// dropping identically. A rock could in theory slip further down than that,
+// so this is a heuristic that holds for the puzzle inputs rather than a proof.
+const SURFACE_DEPTH: usize = 32;
+
 type Cave = VecDeque<Vec<Option<RockType>>>;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -68,7 +73,8 @@ enum RockType {
     Solid,
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    let render = arguments.render.options();
     let horizontal = Rock(vec![vec![Some(()), Some(()), Some(()), Some(())]]);
     let plus = Rock(vec![
         vec![None, Some(()), None],
@@ -87,148 +93,156 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
         vec![Some(())],
     ]);
     let square = Rock(vec![vec![Some(()), Some(())], vec![Some(()), Some(())]]);
-    let wind_length = input.len();
+    let rocks = [horizontal, plus, chair, vertical, square];
+
+    let chamber = Chamber {
+        cave: VecDeque::from(vec![vec![None, None, None, None, None, None, None]]),
+        trimmed_height: 0,
+        rock_index: 0,
+        wind_index: 0,
+        dropped: 0,
+    };
+
+    let cycle_run = find_cycle(
+        chamber,
+        arguments.n,
+        |chamber| {
+            drop_rock(chamber, &rocks, &input);
+            if let Some(render) = &render {
+                if render.is_render_step(chamber.dropped) {
+                    render.print(&format!("After {} rocks", chamber.dropped), chamber);
+                }
+            }
+        },
+        surface_key,
+        |chamber| chamber.trimmed_height + get_tallest_rock_from_cave(&chamber.cave),
+    );
+
+    if let Some(cycle) = cycle_run.cycle {
+        debug!(
+            rocks = cycle.offset + cycle.period,
+            period = cycle.period,
+            "Cycle detected"
+        );
+    }
 
-    let rocks = [horizontal, plus, chair, vertical, square]
-        .into_iter()
-        .cycle();
-    let wind = input.into_iter().cycle();
+    if let Some(render) = &render {
+        if !render.is_render_step(cycle_run.state.dropped) {
+            render.print(
+                &format!("After {} rocks", cycle_run.state.dropped),
+                &cycle_run.state,
+            );
+        }
+        render.finish()?;
+    }
 
-    let cave: Cave = VecDeque::from(vec![vec![None, None, None, None, None, None, None]]);
+    cycle_run.metric_at(arguments.n)
+}
 
-    drop_rocks(arguments.n, cave, rocks, wind, wind_length)
+#[derive(Debug, Clone)]
+struct Chamber {
+    cave: Cave,
+    trimmed_height: usize,
+    rock_index: usize,
+    wind_index: usize,
+    dropped: usize,
 }
 
-fn drop_rocks(
-    n: usize,
-    mut cave: Cave,
-    mut rocks: impl Iterator<Item = Rock>,
-    mut wind: impl Iterator<Item = PointDirection>,
-    wind_loop: usize,
-) -> usize {
-    let mut count = 0;
-    let mut wind_count = 0;
-    let mut prev_count = 0;
-    let mut cycle_count = 0;
-    let mut prev_i = 0;
-    let mut consecutive_count_equal = 0isize;
-    let mut current_cycle_count = 0usize;
-    let mut target_cycle_size = 1usize;
-
-    let mut wind_indexes = HashSet::<usize>::new();
-    let mut prev_wind_indexes = HashSet::<usize>::new();
-    let mut wind_index_match_count = 0isize;
-    let mut current_wind_cycle_count = 0usize;
-    let mut target_wind_cycle_size = 1usize;
-    let mut valid_indexes_found = false;
-    let mut wind_index = 0;
-
-    let mut i = 0;
-
-    while i < n {
-        let next_rock = rocks.next().expect("Rock exists");
-        cave = add_rock(cave, next_rock.clone());
-        let mut rock_complete = false;
-
-        while !rock_complete {
-            let wind_direction = wind.next().expect("window blows");
-            wind_count += 1;
-            if can_move_rock(&cave, &wind_direction) {
-                cave = move_rock(cave, wind_direction)
-            }
+impl Grid for Chamber {
+    type Cell = CaveSpace;
 
-            if can_move_rock(&cave, &PointDirection::Up) {
-                cave = move_rock(cave, PointDirection::Up);
-            } else {
-                cave = freeze_rock(cave);
-                if !valid_indexes_found {
-                    wind_indexes.insert(wind_count % wind_loop);
-                } else if valid_indexes_found && wind_count % wind_loop == wind_index {
-                    current_wind_cycle_count += 1;
-                    if current_wind_cycle_count == target_wind_cycle_size {
-                        current_cycle_count += 1;
-
-                        if current_cycle_count == target_cycle_size {
-                            if cycle_count == count - prev_count {
-                                consecutive_count_equal += 1;
-                            } else {
-                                consecutive_count_equal -= 1;
-                            }
-
-                            if consecutive_count_equal == -3 {
-                                target_cycle_size += 1;
-                                consecutive_count_equal = 0;
-                            } else if consecutive_count_equal == 3 {
-                                let cycle_length = i - prev_i;
-                                println!("Cycle detected after {} iterations. Cycle has a size of {} and a length of {}", i, cycle_count, cycle_length);
-                                let cycle_values = (n - i) / cycle_length;
-                                count += cycle_values * cycle_count;
-                                i += cycle_values * cycle_length;
-                            }
-
-                            current_cycle_count = 0;
-                            cycle_count = count - prev_count;
-                            prev_count = count;
-                            prev_i = i;
-                        }
-
-                        current_wind_cycle_count = 0;
-                    }
-                }
-                rock_complete = true;
-            };
-
-            if !valid_indexes_found && wind_count % wind_loop == 0 {
-                current_wind_cycle_count += 1;
-
-                if current_wind_cycle_count == target_wind_cycle_size {
-                    if wind_indexes == prev_wind_indexes {
-                        wind_index_match_count += 1;
-                    } else {
-                        wind_index_match_count -= 1;
-                    }
-
-                    prev_wind_indexes = wind_indexes;
-                    wind_indexes = HashSet::new();
-
-                    if wind_index_match_count == 3 {
-                        wind_index = *prev_wind_indexes
-                            .iter()
-                            .next()
-                            .expect("At least one matching");
-                        valid_indexes_found = true;
-                        println!(
-                            "Found stable wind drop cycles every {} wind cycles. Using: {} to find rock cycles",
-                            target_wind_cycle_size, wind_index
-                        );
-                        prev_count = 0;
-                        prev_i = 0;
-                    } else if wind_index_match_count == -3 {
-                        target_wind_cycle_size += 1;
-                        wind_index_match_count = 0;
-                    }
-
-                    current_wind_cycle_count = 0;
-                }
-            }
+    fn viewport(&self) -> Viewport {
+        Viewport {
+            min_x: 0,
+            min_y: 0,
+            max_x: 6,
+            max_y: self.cave.len() as isize - 1,
         }
+    }
 
-        if i % 50 == 0 {
-            let check_split_points = (0..7)
-                .into_iter()
-                .map(|index| get_tallest_rock_from_cave_at_index(&cave, index))
-                .collect::<Vec<_>>();
-            if check_split_points.iter().all(|point| point > &0) {
-                let split_point = check_split_points.into_iter().min().expect("at least one");
-                count += split_point - 1;
-                cave = cave.split_off(split_point - 1);
-            }
+    fn cell(&self, x: isize, y: isize) -> CaveSpace {
+        CaveSpace(
+            self.cave
+                .get(self.cave.len() - 1 - y as usize)
+                .and_then(|row| row.get(x as usize))
+                .cloned()
+                .flatten(),
+        )
+    }
+}
+
+struct CaveSpace(Option<RockType>);
+
+impl RenderCell for CaveSpace {
+    fn to_char(&self) -> char {
+        match self.0 {
+            Some(RockType::Active) => '@',
+            Some(RockType::Solid) => '#',
+            None => '.',
+        }
+    }
+
+    fn color(&self) -> Option<Color> {
+        match self.0 {
+            Some(RockType::Active) => Some(Color::Red),
+            Some(RockType::Solid) => Some(Color::Yellow),
+            None => None,
         }
+    }
+}
+
+fn drop_rock(chamber: &mut Chamber, rocks: &[Rock], wind: &[PointDirection]) {
+    let next_rock = rocks.get(chamber.rock_index).expect("Rock exists");
+    chamber.rock_index = (chamber.rock_index + 1) % rocks.len();
+    let mut cave = add_rock(std::mem::take(&mut chamber.cave), next_rock.clone());
 
-        i += 1;
+    loop {
+        let wind_direction = wind.get(chamber.wind_index).expect("window blows");
+        chamber.wind_index = (chamber.wind_index + 1) % wind.len();
+        if can_move_rock(&cave, wind_direction) {
+            cave = move_rock(cave, *wind_direction)
+        }
+
+        if can_move_rock(&cave, &PointDirection::Up) {
+            cave = move_rock(cave, PointDirection::Up);
+        } else {
+            cave = freeze_rock(cave);
+            break;
+        }
+    }
+
+    if chamber.dropped.is_multiple_of(50) {
+        let check_split_points = (0..7)
+            .map(|index| get_tallest_rock_from_cave_at_index(&cave, index))
+            .collect::<Vec<_>>();
+        if check_split_points.iter().all(|point| point > &0) {
+            let split_point = check_split_points.into_iter().min().expect("at least one");
+            chamber.trimmed_height += split_point - 1;
+            cave = cave.split_off(split_point - 1);
+        }
     }
 
-    count + get_tallest_rock_from_cave(&cave)
+    chamber.dropped += 1;
+    chamber.cave = cave;
+}
+
+fn surface_key(chamber: &Chamber) -> (usize, usize, Vec<u8>) {
+    let tallest_rock = get_tallest_rock_from_cave(&chamber.cave);
+    let surface = chamber
+        .cave
+        .iter()
+        .take(tallest_rock)
+        .rev()
+        .take(SURFACE_DEPTH)
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, space)| space.is_some())
+                .fold(0u8, |acc, (x, _)| acc | (1 << x))
+        })
+        .collect();
+
+    (chamber.rock_index, chamber.wind_index, surface)
 }
 
 fn freeze_rock(mut cave: Cave) -> Cave {
@@ -375,18 +389,12 @@ fn add_height_padding(mut cave: Cave) -> Cave {
     cave
 }
 
-fn _print_cave(cave: &Cave) {
-    for y in (0..cave.len()).rev() {
-        let row = cave.get(y).expect("Row exists");
-        println!(
-            "{}",
-            row.iter()
-                .map(|value| match value {
-                    Some(RockType::Active) => "@",
-                    Some(RockType::Solid) => "#",
-                    None => ".",
-                })
-                .join("")
-        )
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_rejects_other_directions() {
+        assert!(parse_file("<>^\n".to_string()).is_err());
     }
 }
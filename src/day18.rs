@@ -1,28 +1,24 @@
-use adventofcode2022::{flag_arg, parse_isize, parse_lines, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{
+    flood_fill_3d, parse_isize, parse_lines, BoundingBox3, Command, ParseError, Point3, Problem,
+};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
-use itertools::Itertools;
+use clap::Args;
 use std::{cell::LazyCell, collections::HashSet};
 
-type ParseOutput = Vec<Point3d>;
+type ParseOutput = Vec<Point3<isize>>;
+
+const MAX_CUBES: usize = 100_000;
 
 pub const DAY_18: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let air = flag_arg(
-        "air",
-        'a',
-        "Account for air bubble when finding the exposed surface area.",
-    );
     let problem = Problem::new(
         "day18",
         "Finds the total surface area of lava drops.",
         "Path to the input file. File should consist of one 3d coordinate of lava per line.",
-        vec![air],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -37,61 +33,16 @@ pub const DAY_18: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(
+        short,
+        long,
+        help = "Account for air bubble when finding the exposed surface area."
+    )]
     air: bool,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        air: *args.get_one::<bool>("air").unwrap_or(&false),
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Point3d {
-    x: isize,
-    y: isize,
-    z: isize,
-}
-
-impl Point3d {
-    fn get_adjacent_points(&self) -> Vec<Point3d> {
-        vec![
-            Point3d {
-                x: self.x + 1,
-                y: self.y,
-                z: self.z,
-            },
-            Point3d {
-                x: self.x - 1,
-                y: self.y,
-                z: self.z,
-            },
-            Point3d {
-                x: self.x,
-                y: self.y + 1,
-                z: self.z,
-            },
-            Point3d {
-                x: self.x,
-                y: self.y - 1,
-                z: self.z,
-            },
-            Point3d {
-                x: self.x,
-                y: self.y,
-                z: self.z + 1,
-            },
-            Point3d {
-                x: self.x,
-                y: self.y,
-                z: self.z - 1,
-            },
-        ]
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -102,131 +53,92 @@ fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
     parse_lines(parse_point()).then_ignore(end())
 }
 
-fn parse_point() -> impl Parser<char, Point3d, Error = Simple<char>> {
+fn parse_point() -> impl Parser<char, Point3<isize>, Error = Simple<char>> {
     parse_isize()
         .then_ignore(just(","))
         .then(parse_isize())
         .then_ignore(just(","))
         .then(parse_isize())
-        .map(|((x, y), z)| Point3d { x, y, z })
+        .map(|((x, y), z)| Point3::new(x, y, z))
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
-    let points = input.iter().cloned().collect::<HashSet<_>>();
-    let (min_x, max_x) = match input.iter().map(|point| point.x).minmax() {
-        itertools::MinMaxResult::NoElements => (0, 0),
-        itertools::MinMaxResult::OneElement(minmax) => (minmax, minmax),
-        itertools::MinMaxResult::MinMax(min, max) => (min, max),
-    };
-    let (min_y, max_y) = match input.iter().map(|point| point.y).minmax() {
-        itertools::MinMaxResult::NoElements => (0, 0),
-        itertools::MinMaxResult::OneElement(minmax) => (minmax, minmax),
-        itertools::MinMaxResult::MinMax(min, max) => (min, max),
-    };
-    let (min_z, max_z) = match input.iter().map(|point| point.z).minmax() {
-        itertools::MinMaxResult::NoElements => (0, 0),
-        itertools::MinMaxResult::OneElement(minmax) => (minmax, minmax),
-        itertools::MinMaxResult::MinMax(min, max) => (min, max),
+fn check_bounds(input: &ParseOutput, air: bool) -> Result<()> {
+    let Some(bounds) = BoundingBox3::from_points(input) else {
+        return Ok(());
     };
+    let edges = [
+        (bounds.min.x, bounds.max.x),
+        (bounds.min.y, bounds.max.y),
+        (bounds.min.z, bounds.max.z),
+    ];
+
+    if edges
+        .iter()
+        .any(|(min, max)| min.checked_sub(1).is_none() || max.checked_add(1).is_none())
+    {
+        return Err(anyhow!("The lava has no room around it for air"));
+    }
+    if !air {
+        return Ok(());
+    }
 
-    let mut known_escape_points = HashSet::new();
-    let mut known_trap_points = HashSet::new();
+    edges
+        .into_iter()
+        .try_fold(1usize, |cubes, (min, max)| {
+            cubes.checked_mul(max.abs_diff(min).checked_add(3)?)
+        })
+        .filter(|cubes| *cubes <= MAX_CUBES)
+        .map(|_| ())
+        .ok_or_else(|| anyhow!("The lava does not fit in a box of {} cubes", MAX_CUBES))
+}
 
-    points
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    check_bounds(&input, arguments.air)?;
+    let points = input.iter().cloned().collect::<HashSet<_>>();
+    let exterior = if arguments.air {
+        BoundingBox3::from_points(&input).map(|bounds| {
+            let bounds = bounds.expand(1);
+            flood_fill_3d(bounds.min, &bounds, |point| !points.contains(point))
+        })
+    } else {
+        None
+    };
+
+    Ok(points
         .iter()
-        .map(|this| {
-            let adjacents = this
-                .get_adjacent_points()
-                .into_iter()
-                .filter(|point| points.contains(point))
-                .collect::<HashSet<_>>();
-
-            let air_count = if arguments.air {
-                this.get_adjacent_points()
-                    .into_iter()
-                    .filter(|point| !adjacents.contains(&point))
-                    .filter(|air| {
-                        let mut visited = HashSet::new();
-                        let result = is_air_bubble(
-                            air,
-                            &min_x,
-                            &max_x,
-                            &min_y,
-                            &max_y,
-                            &min_z,
-                            &max_z,
-                            &points,
-                            &known_escape_points,
-                            &known_trap_points,
-                            &mut visited,
-                        );
-
-                        if result {
-                            known_trap_points.extend(visited.into_iter());
-                        } else {
-                            known_escape_points.extend(visited.into_iter());
-                        }
-                        result
-                    })
-                    .count()
-            } else {
-                0
-            };
-
-            6 - (adjacents.len() + air_count)
+        .flat_map(|point| point.into_iter_face_adjacent())
+        .filter(|adjacent| match &exterior {
+            Some(exterior) => exterior.contains(adjacent),
+            None => !points.contains(adjacent),
         })
-        .sum()
+        .count())
 }
 
-fn is_air_bubble(
-    point: &Point3d,
-    min_x: &isize,
-    max_x: &isize,
-    min_y: &isize,
-    max_y: &isize,
-    min_z: &isize,
-    max_z: &isize,
-    lava_points: &HashSet<Point3d>,
-    known_escape_points: &HashSet<Point3d>,
-    known_trap_points: &HashSet<Point3d>,
-    visited: &mut HashSet<Point3d>,
-) -> bool {
-    visited.insert(point.clone());
-    if known_escape_points.contains(&point) {
-        return false;
-    } else if known_trap_points.contains(&point) {
-        return true;
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if point.x >= *max_x
-        || point.y >= *max_y
-        || point.z >= *max_z
-        || point.x <= *min_x
-        || point.y <= *min_y
-        || point.z <= *min_z
-    {
-        return false;
+    #[test]
+    fn parse_point_reads_negative_coordinates() {
+        assert_eq!(parse_point().parse("-1,2,-3"), Ok(Point3::new(-1, 2, -3)));
     }
 
-    let new_points = point
-        .get_adjacent_points()
-        .into_iter()
-        .filter(|point| !(visited.contains(point) || lava_points.contains(&point)))
-        .collect::<Vec<_>>();
-
-    new_points.into_iter().all(|point| {
-        is_air_bubble(
-            &point,
-            min_x,
-            max_x,
-            min_y,
-            max_y,
-            min_z,
-            max_z,
-            lava_points,
-            known_escape_points,
-            known_trap_points,
-            visited,
-        )
-    })
+    #[test]
+    fn run_rejects_lava_outside_the_cube_limit() {
+        let far = vec![Point3::new(0, 0, 0), Point3::new(0, 0, MAX_CUBES as isize)];
+        let edge = vec![Point3::new(isize::MAX, 0, 0)];
+
+        assert!(run(far.clone(), CommandLineArguments { air: true }).is_err());
+        assert_eq!(run(far, CommandLineArguments { air: false }).unwrap(), 12);
+        assert!(run(edge.clone(), CommandLineArguments { air: true }).is_err());
+        assert!(run(edge, CommandLineArguments { air: false }).is_err());
+        assert_eq!(
+            run(
+                vec![Point3::new(1, 1, 1)],
+                CommandLineArguments { air: true }
+            )
+            .unwrap(),
+            6
+        );
+    }
 }
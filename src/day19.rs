@@ -1,38 +1,26 @@
-use adventofcode2022::{parse_lines, parse_usize, single_arg, Command, ParseError, Problem};
+use adventofcode2022::{parse_lines, parse_usize, Command, ParseError, Problem};
 use anyhow::Result;
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::{ArgMatches, ValueEnum};
+use clap::{Args, ValueEnum};
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 use std::{
     cell::LazyCell,
     cmp::{max, min},
     collections::HashMap,
 };
+use tracing::{debug, Span};
 
 type ParseOutput = Vec<Blueprint>;
 
 pub const DAY_19: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let time = single_arg("time", 't', "The time you have to crack geodes")
-        .value_parser(clap::value_parser!(u16));
-    let limit = single_arg("limit", 'l', "Limits the number of blueprints to check")
-        .required(false)
-        .value_parser(clap::value_parser!(usize));
-    let stats = single_arg(
-        "stats",
-        's',
-        "What metric shoudl be determined for the blueprints",
-    )
-    .value_parser(clap::value_parser!(BlueprintStats));
     let problem = Problem::new(
         "day19",
         "Finds the maximum amount of geodes you can crack with a given recipe.",
         "Path to the input file. Each line should contain a recpiee for how to constuct robots of all types.",
-        vec![time, limit, stats],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -47,24 +35,21 @@ pub enum BlueprintStats {
     ProductGeodes,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The time you have to crack geodes")]
     time: u16,
+    #[arg(short, long, help = "Limits the number of blueprints to check")]
     limit: Option<usize>,
+    #[arg(
+        short = 's',
+        long = "stats",
+        value_name = "STATS",
+        help = "What metric shoudl be determined for the blueprints"
+    )]
     blueprint_stats: BlueprintStats,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        time: *args.get_one::<u16>("time").expect("Valid arguments"),
-        limit: args.get_one::<usize>("limit").copied(),
-        blueprint_stats: args
-            .get_one::<BlueprintStats>("stats")
-            .expect("Valid arguments")
-            .clone(),
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Material {
     Ore,
@@ -72,29 +57,29 @@ pub enum Material {
     Obsidian,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OreRobot {
     cost: Cost,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ClayRobot {
     cost: Cost,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObsidianRobot {
     cost1: Cost,
     cost2: Cost,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GeodeRobot {
     cost1: Cost,
     cost2: Cost,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Blueprint {
     id: u16,
     ore: OreRobot,
@@ -103,7 +88,7 @@ pub struct Blueprint {
     geode: GeodeRobot,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Cost {
     material: Material,
     cost: u16,
@@ -198,6 +183,7 @@ fn parse_material() -> impl Parser<char, Material, Error = Simple<char>> {
 }
 
 fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+    let span = Span::current();
     let mut count = 0;
     let result = input
         .into_iter()
@@ -207,7 +193,11 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
             result
         })
         .par_bridge()
-        .map(|blueprint| score_blueprint(&blueprint, arguments.time, &arguments.blueprint_stats));
+        .map(|blueprint| {
+            span.in_scope(|| {
+                score_blueprint(&blueprint, arguments.time, &arguments.blueprint_stats)
+            })
+        });
 
     match arguments.blueprint_stats {
         BlueprintStats::QualityLevelSum => result.sum::<u16>() as usize,
@@ -226,27 +216,45 @@ fn score_blueprint(blueprint: &Blueprint, time: u16, blueprint_stats: &Blueprint
         geode_robots: 0,
     };
 
-    let result = if blueprint_stats == &BlueprintStats::QualityLevelSum {
-        blueprint.id
-    } else {
-        1
-    } * get_number_of_geodes_cracked(
+    let mut memo = Memo::default();
+    let geodes = get_number_of_geodes_cracked(
         blueprint,
         time,
         start_resources,
-        &mut HashMap::new(),
+        &mut memo,
         &get_max_material(blueprint, &Material::Ore),
         &get_max_material(blueprint, &Material::Clay),
         &get_max_material(blueprint, &Material::Obsidian),
     );
-    result
+    debug!(
+        blueprint = blueprint.id,
+        geodes,
+        states = memo.states.len(),
+        hits = memo.hits,
+        pruned = memo.pruned,
+        "Scored blueprint"
+    );
+
+    let multiplier = if blueprint_stats == &BlueprintStats::QualityLevelSum {
+        blueprint.id
+    } else {
+        1
+    };
+    multiplier * geodes
+}
+
+#[derive(Default)]
+struct Memo {
+    states: HashMap<(u16, Resources), u16>,
+    hits: usize,
+    pruned: usize,
 }
 
 fn get_number_of_geodes_cracked(
     blueprint: &Blueprint,
     time: u16,
     resources: Resources,
-    cache: &mut HashMap<(u16, Resources), u16>,
+    cache: &mut Memo,
     max_ore: &u16,
     max_clay: &u16,
     max_obsidion: &u16,
@@ -255,26 +263,36 @@ fn get_number_of_geodes_cracked(
         return 0;
     }
 
-    match cache.get(&(time, resources.clone())) {
-        Some(value) => *value,
+    match cache.states.get(&(time, resources.clone())) {
+        Some(value) => {
+            cache.hits += 1;
+            *value
+        }
         None => {
             let result = resources.geode_robots
-                + get_purchases(blueprint, &resources, &max_ore, &max_clay, &max_obsidion)
-                    .into_iter()
-                    .map(|new_resources| {
-                        get_number_of_geodes_cracked(
-                            blueprint,
-                            time - 1,
-                            new_resources,
-                            cache,
-                            max_ore,
-                            max_clay,
-                            max_obsidion,
-                        )
-                    })
-                    .max()
-                    .unwrap_or(0);
-            cache.insert((time, resources), result);
+                + get_purchases(
+                    blueprint,
+                    &resources,
+                    &max_ore,
+                    &max_clay,
+                    &max_obsidion,
+                    &mut cache.pruned,
+                )
+                .into_iter()
+                .map(|new_resources| {
+                    get_number_of_geodes_cracked(
+                        blueprint,
+                        time - 1,
+                        new_resources,
+                        cache,
+                        max_ore,
+                        max_clay,
+                        max_obsidion,
+                    )
+                })
+                .max()
+                .unwrap_or(0);
+            cache.states.insert((time, resources), result);
             result
         }
     }
@@ -328,10 +346,21 @@ fn get_purchases(
     max_ore: &u16,
     max_clay: &u16,
     max_obsidion: &u16,
+    pruned: &mut usize,
 ) -> Vec<Resources> {
-    let make_ore = if get_purchasable_ore_robots(blueprint, resources) > 0
-        && &resources.ore_robots < max_ore
-    {
+    let can_make_ore = get_purchasable_ore_robots(blueprint, resources) > 0;
+    let can_make_clay = get_purchasable_clay_robots(blueprint, resources) > 0;
+    let can_make_obsidian = get_purchasable_obsidian_robots(blueprint, resources) > 0;
+    *pruned += [
+        can_make_ore && &resources.ore_robots >= max_ore,
+        can_make_clay && &resources.clay_robots >= max_clay,
+        can_make_obsidian && &resources.obsidian_robots >= max_obsidion,
+    ]
+    .into_iter()
+    .filter(|capped| *capped)
+    .count();
+
+    let make_ore = if can_make_ore && &resources.ore_robots < max_ore {
         let mut new_ore_resources = resources.clone();
         match blueprint.ore.cost.material {
             Material::Ore => new_ore_resources.ore -= blueprint.ore.cost.cost,
@@ -344,9 +373,7 @@ fn get_purchases(
         None
     };
 
-    let make_clay = if get_purchasable_clay_robots(blueprint, resources) > 0
-        && &resources.clay_robots < max_clay
-    {
+    let make_clay = if can_make_clay && &resources.clay_robots < max_clay {
         let mut new_clay_resources = resources.clone();
         match blueprint.clay.cost.material {
             Material::Ore => new_clay_resources.ore -= blueprint.clay.cost.cost,
@@ -360,9 +387,7 @@ fn get_purchases(
         None
     };
 
-    let make_obsidian = if get_purchasable_obsidian_robots(blueprint, resources) > 0
-        && &resources.obsidian_robots < max_obsidion
-    {
+    let make_obsidian = if can_make_obsidian && &resources.obsidian_robots < max_obsidion {
         let mut new_obsidian_resources = resources.clone();
         match blueprint.obsidian.cost1.material {
             Material::Ore => new_obsidian_resources.ore -= blueprint.obsidian.cost1.cost,
@@ -400,9 +425,9 @@ fn get_purchases(
         None
     };
 
-    let base_resources = if get_purchasable_ore_robots(blueprint, resources) > 0
-        && get_purchasable_clay_robots(blueprint, resources) > 0
-        && get_purchasable_obsidian_robots(blueprint, resources) > 0
+    let base_resources = if can_make_ore
+        && can_make_clay
+        && can_make_obsidian
         && get_purchasable_geode_robots(blueprint, resources) > 0
     {
         None
@@ -470,3 +495,33 @@ fn get_purchasable_geode_robots(blueprint: &Blueprint, resources: &Resources) ->
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cost_reads_each_material() {
+        assert_eq!(
+            parse_cost().parse("4 ore"),
+            Ok(Cost {
+                material: Material::Ore,
+                cost: 4
+            })
+        );
+        assert_eq!(
+            parse_cost().parse("14 clay"),
+            Ok(Cost {
+                material: Material::Clay,
+                cost: 14
+            })
+        );
+        assert_eq!(
+            parse_cost().parse("7 obsidian"),
+            Ok(Cost {
+                material: Material::Obsidian,
+                cost: 7
+            })
+        );
+    }
+}
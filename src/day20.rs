@@ -1,22 +1,16 @@
-use adventofcode2022::{parse_isize, parse_lines, single_arg, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{parse_isize, parse_lines, Command, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{prelude::Simple, primitive::end, Parser};
-use clap::ArgMatches;
+use clap::Args;
 use std::{cell::LazyCell, fmt::Debug};
 
 type ParseOutput = Vec<isize>;
 
 pub const DAY_20: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let iterations = single_arg("iterations", 'i', "The number of times to remix the file")
-        .value_parser(clap::value_parser!(usize));
-    let decryption_key = single_arg("key", 'k', "The key to decrypt the file.")
-        .value_parser(clap::value_parser!(isize));
     let problem = Problem::new(
         "day20",
         "Finds the grove coordinates based on the encrypted file and sums them.",
         "Path to the input file. A newline delimited list of numbers that are mixed up.",
-        vec![iterations, decryption_key],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -37,21 +31,19 @@ pub const DAY_20: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The number of times to remix the file")]
     iterations: usize,
+    #[arg(
+        short = 'k',
+        long = "key",
+        value_name = "KEY",
+        help = "The key to decrypt the file."
+    )]
     decryption_key: isize,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        iterations: *args
-            .get_one::<usize>("iterations")
-            .expect("Valid arguments"),
-        decryption_key: *args.get_one::<isize>("key").expect("Valid arguments"),
-    }
-}
-
 fn parse_file(file: String) -> Result<ParseOutput> {
     parser()
         .parse(file.clone())
@@ -59,15 +51,33 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_isize()).then_ignore(end())
+    parse_lines(parse_isize())
+        .then_ignore(end())
+        .try_map(|numbers, span| {
+            match (
+                numbers.len(),
+                numbers.iter().filter(|value| **value == 0).count(),
+            ) {
+                (0..=1, _) => Err(Simple::custom(
+                    span,
+                    "The file must have at least 2 numbers",
+                )),
+                (_, 1) => Ok(numbers),
+                _ => Err(Simple::custom(span, "The file must have exactly one 0")),
+            }
+        })
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<isize> {
     let decryption_key = arguments.decryption_key;
     let input = input
         .into_iter()
-        .map(|value| value * decryption_key)
-        .collect::<Vec<_>>();
+        .map(|value| {
+            value
+                .checked_mul(decryption_key)
+                .ok_or_else(|| anyhow!("{} overflows when decrypted", value))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
     let max_index = input.len();
     let max_isize_index = max_index as isize;
@@ -82,7 +92,8 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
             let value = value;
             let old = indexes.iter().position(|i| &index == i).expect("exists");
             indexes.remove(old);
-            let new = (old as isize + value).rem_euclid(max_isize_index - 1);
+            let new = (old as isize + value.rem_euclid(max_isize_index - 1))
+                .rem_euclid(max_isize_index - 1);
             indexes.insert(new as usize, index);
         });
     }
@@ -101,5 +112,88 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
     let second = result.get((zero_index + 2000) % max_index).expect("Exists");
     let third = result.get((zero_index + 3000) % max_index).expect("Exists");
 
-    *first + *second + *third
+    first
+        .checked_add(**second)
+        .and_then(|sum| sum.checked_add(**third))
+        .ok_or_else(|| anyhow!("The grove coordinates overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{property, Rng};
+
+    fn generate_input(rng: &mut Rng, size: usize) -> ParseOutput {
+        let size = size as isize;
+        let mut numbers = (0..rng.usize_in(2..=size.max(2) as usize))
+            .map(|_| {
+                let value = rng.isize_in(1..=size * 3);
+                if rng.bool() {
+                    value
+                } else {
+                    -value
+                }
+            })
+            .collect::<Vec<_>>();
+        numbers.push(0);
+        rng.shuffle(&mut numbers);
+        numbers
+    }
+
+    fn reference(input: &[isize], iterations: usize, decryption_key: isize) -> isize {
+        let mut ring = input
+            .iter()
+            .map(|value| value * decryption_key)
+            .enumerate()
+            .collect::<Vec<_>>();
+        let length = ring.len();
+
+        (0..iterations).for_each(|_| {
+            (0..length).for_each(|original| {
+                let mut position = ring
+                    .iter()
+                    .position(|(index, _)| *index == original)
+                    .expect("Number exists");
+                let value = ring[position].1;
+                (0..(value % (length as isize - 1)).abs()).for_each(|_| {
+                    let next = if value > 0 {
+                        (position + 1) % length
+                    } else {
+                        (position + length - 1) % length
+                    };
+                    ring.swap(position, next);
+                    position = next;
+                });
+            });
+        });
+
+        let zero = ring
+            .iter()
+            .position(|(_, value)| *value == 0)
+            .expect("0 exists");
+        [1000, 2000, 3000]
+            .into_iter()
+            .map(|offset| ring[(zero + offset) % length].1)
+            .sum()
+    }
+
+    #[test]
+    fn mixing_matches_reference() {
+        property(100, 30, |rng, size| {
+            let input = generate_input(rng, size);
+            [(1, 1), (10, 811_589_153)]
+                .into_iter()
+                .for_each(|(iterations, decryption_key)| {
+                    let arguments = CommandLineArguments {
+                        iterations,
+                        decryption_key,
+                    };
+
+                    assert_eq!(
+                        run(input.clone(), arguments).ok(),
+                        Some(reference(&input, iterations, decryption_key))
+                    );
+                });
+        });
+    }
 }
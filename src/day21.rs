@@ -1,23 +1,23 @@
-use adventofcode2022::{flag_arg, parse_isize, parse_lines, Command, ParseError, Problem};
-use anyhow::Result;
+use adventofcode2022::{parse_isize, parse_lines, Command, ParseError, Problem};
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     text, Parser,
 };
-use clap::ArgMatches;
-use std::{cell::LazyCell, collections::HashMap};
+use clap::Args;
+use std::{
+    cell::LazyCell,
+    collections::{HashMap, HashSet},
+};
 
 type ParseOutput = Vec<Monkey>;
 
 pub const DAY_21: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let equal = flag_arg("equal", 'e', "The number of elves to sum");
     let problem = Problem::new(
         "day21",
         "Evaludates the monkey expression",
         "Path to the input file. Each line has a monkey name, followed by an expression.",
-        vec![equal],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -32,30 +32,25 @@ pub const DAY_21: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The number of elves to sum")]
     equal: bool,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        equal: *args.get_one::<bool>("equal").unwrap_or(&false),
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Monkey {
     name: String,
     operation: Operation,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operation {
     Value(isize),
     Experssion(String, String, Operator),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operator {
     Add,
     Sub,
@@ -70,7 +65,58 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_monkey()).then_ignore(end())
+    parse_lines(parse_monkey())
+        .then_ignore(end())
+        .try_map(|monkeys, span| {
+            validate_monkeys(&monkeys).map_err(|message| Simple::custom(span, message))?;
+            Ok(monkeys)
+        })
+}
+
+fn validate_monkeys(monkeys: &[Monkey]) -> Result<(), String> {
+    let operations = monkeys
+        .iter()
+        .map(|monkey| (monkey.name.as_str(), &monkey.operation))
+        .collect::<HashMap<_, _>>();
+
+    if operations.len() != monkeys.len() {
+        return Err("Every monkey must have a unique name".to_string());
+    }
+    if let Some(name) = ["root", "humn"]
+        .into_iter()
+        .find(|name| !operations.contains_key(name))
+    {
+        return Err(format!("There must be a monkey named {}", name));
+    }
+
+    let mut resolved = HashSet::new();
+    operations.keys().try_for_each(|name| {
+        let mut stack = vec![(*name, false)];
+        let mut visiting = HashSet::new();
+        while let Some((name, expanded)) = stack.pop() {
+            if expanded {
+                visiting.remove(name);
+                resolved.insert(name);
+                continue;
+            }
+            if resolved.contains(name) {
+                continue;
+            }
+            if !visiting.insert(name) {
+                return Err(format!("Monkey {} depends on itself", name));
+            }
+            stack.push((name, true));
+            match operations.get(name) {
+                None => return Err(format!("Monkey {} does not exist", name)),
+                Some(Operation::Value(_)) => (),
+                Some(Operation::Experssion(left, right, _)) => {
+                    stack.push((left.as_str(), false));
+                    stack.push((right.as_str(), false));
+                }
+            }
+        }
+        Ok(())
+    })
 }
 
 fn parse_monkey() -> impl Parser<char, Monkey, Error = Simple<char>> {
@@ -99,7 +145,7 @@ fn parse_operator() -> impl Parser<char, Operator, Error = Simple<char>> {
     add.or(sub).or(mul).or(div)
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<isize> {
     let mut monkeys = input
         .into_iter()
         .map(|monkey| (monkey.name.clone(), monkey))
@@ -111,88 +157,121 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> isize {
         match root.operation {
             Operation::Experssion(left, right, _) => {
                 match (
-                    evaluate_monkey_opt(&left, &monkeys),
-                    evaluate_monkey_opt(&right, &monkeys),
+                    evaluate_monkey_opt(&left, &monkeys)?,
+                    evaluate_monkey_opt(&right, &monkeys)?,
                 ) {
                     (None, Some(target)) => resolve_to_target(&left, target, &monkeys),
                     (Some(target), None) => resolve_to_target(&right, target, &monkeys),
-                    (None, None) => 0,
-                    (Some(_), Some(_)) => 0,
+                    (None, None) => Ok(0),
+                    (Some(_), Some(_)) => Ok(0),
                 }
             }
-            Operation::Value(value) => value,
+            Operation::Value(value) => Ok(value),
         }
     } else {
         evaluate_monkey(&"root".to_string(), &monkeys)
     }
 }
 
-fn evaluate_monkey(name: &String, monkeys: &HashMap<String, Monkey>) -> isize {
+fn apply(operator: &Operator, left: isize, right: isize) -> Result<isize> {
+    match operator {
+        Operator::Add => left.checked_add(right),
+        Operator::Sub => left.checked_sub(right),
+        Operator::Mul => left.checked_mul(right),
+        Operator::Div => left.checked_div(right),
+    }
+    .ok_or_else(|| anyhow!("Cannot evaluate {} {:?} {}", left, operator, right))
+}
+
+fn evaluate_monkey(name: &String, monkeys: &HashMap<String, Monkey>) -> Result<isize> {
     let current = monkeys.get(name).expect("Monkey exists");
 
     match &current.operation {
-        Operation::Value(value) => *value,
-        Operation::Experssion(sub1, sub2, operator) => match operator {
-            Operator::Add => evaluate_monkey(&sub1, monkeys) + evaluate_monkey(&sub2, monkeys),
-            Operator::Sub => evaluate_monkey(&sub1, monkeys) - evaluate_monkey(&sub2, monkeys),
-            Operator::Mul => evaluate_monkey(&sub1, monkeys) * evaluate_monkey(&sub2, monkeys),
-            Operator::Div => evaluate_monkey(&sub1, monkeys) / evaluate_monkey(&sub2, monkeys),
-        },
+        Operation::Value(value) => Ok(*value),
+        Operation::Experssion(sub1, sub2, operator) => apply(
+            operator,
+            evaluate_monkey(sub1, monkeys)?,
+            evaluate_monkey(sub2, monkeys)?,
+        ),
     }
 }
 
-fn evaluate_monkey_opt(name: &String, monkeys: &HashMap<String, Monkey>) -> Option<isize> {
-    monkeys
-        .get(name)
-        .and_then(|current| match &current.operation {
-            Operation::Value(value) => Some(*value),
+fn evaluate_monkey_opt(name: &String, monkeys: &HashMap<String, Monkey>) -> Result<Option<isize>> {
+    match monkeys.get(name) {
+        None => Ok(None),
+        Some(current) => match &current.operation {
+            Operation::Value(value) => Ok(Some(*value)),
             Operation::Experssion(sub1, sub2, operator) => match (
-                evaluate_monkey_opt(&sub1, monkeys),
-                evaluate_monkey_opt(&sub2, monkeys),
+                evaluate_monkey_opt(sub1, monkeys)?,
+                evaluate_monkey_opt(sub2, monkeys)?,
             ) {
-                (Some(left), Some(right)) => Some(match operator {
-                    Operator::Add => left + right,
-                    Operator::Sub => left - right,
-                    Operator::Mul => left * right,
-                    Operator::Div => left / right,
-                }),
-                _ => None,
+                (Some(left), Some(right)) => apply(operator, left, right).map(Some),
+                _ => Ok(None),
             },
-        })
+        },
+    }
 }
 
-fn resolve_to_target(name: &String, target: isize, monkeys: &HashMap<String, Monkey>) -> isize {
+fn resolve_to_target(
+    name: &String,
+    target: isize,
+    monkeys: &HashMap<String, Monkey>,
+) -> Result<isize> {
     if name == "humn" {
-        return target;
+        return Ok(target);
     }
     let current = monkeys.get(name).expect("Monkey exists");
 
     match &current.operation {
-        Operation::Value(_) => 0,
+        Operation::Value(_) => Ok(0),
         Operation::Experssion(left, right, operation) => match (
-            evaluate_monkey_opt(&left, monkeys),
-            evaluate_monkey_opt(&right, monkeys),
+            evaluate_monkey_opt(&left, monkeys)?,
+            evaluate_monkey_opt(&right, monkeys)?,
         ) {
-            (None, None) => 0,
+            (None, None) => Ok(0),
             (None, Some(value)) => {
                 let new_target = match operation {
-                    Operator::Add => target - value,
-                    Operator::Sub => target + value,
-                    Operator::Mul => target / value,
-                    Operator::Div => target * value,
-                };
+                    Operator::Add => apply(&Operator::Sub, target, value),
+                    Operator::Sub => apply(&Operator::Add, target, value),
+                    Operator::Mul => apply(&Operator::Div, target, value),
+                    Operator::Div => apply(&Operator::Mul, target, value),
+                }?;
                 resolve_to_target(&left, new_target, monkeys)
             }
             (Some(value), None) => {
                 let new_target = match operation {
-                    Operator::Add => target - value,
-                    Operator::Sub => value - target,
-                    Operator::Mul => target / value,
-                    Operator::Div => value / target,
-                };
+                    Operator::Add => apply(&Operator::Sub, target, value),
+                    Operator::Sub => apply(&Operator::Sub, value, target),
+                    Operator::Mul => apply(&Operator::Div, target, value),
+                    Operator::Div => apply(&Operator::Div, value, target),
+                }?;
                 resolve_to_target(&right, new_target, monkeys)
             }
-            (Some(_), Some(_)) => 0,
+            (Some(_), Some(_)) => Ok(0),
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_operation_reads_values_and_expressions() {
+        assert_eq!(parse_operation().parse("5"), Ok(Operation::Value(5)));
+        assert_eq!(
+            parse_operation().parse("pppw / sjmn"),
+            Ok(Operation::Experssion(
+                "pppw".to_string(),
+                "sjmn".to_string(),
+                Operator::Div
+            ))
+        );
+    }
+
+    #[test]
+    fn parser_rejects_cyclic_monkeys() {
+        assert!(parse_file("root: humn + aaaa\nhumn: 5\naaaa: root * humn".to_string()).is_err());
+        assert!(parse_file("root: humn + aaaa\nhumn: 5".to_string()).is_err());
+    }
+}
@@ -1,36 +1,28 @@
 use adventofcode2022::{
-    parse_lines, parse_usize, single_arg, BoundedPoint, Command, ParseError, PointDirection,
+    bfs, parse_lines, parse_usize, BoundedPoint, Command, ParseError, Point3, PointDirection,
     Problem, RotationDegrees,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     text, Parser,
 };
-use clap::{value_parser, ArgMatches};
+use clap::Args;
+use itertools::Itertools;
 use std::{
     cell::LazyCell,
-    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    collections::{HashMap, HashSet},
 };
 
 type ParseOutput = (Vec<Vec<Tile>>, Vec<Instruction>);
+type RegionMappings = HashMap<usize, HashMap<PointDirection, (usize, RotationDegrees)>>;
 
 pub const DAY_22: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let region_size = single_arg(
-        "region",
-        'r',
-        "The size of the cube faces if folded up into a cube",
-    )
-    .required(false)
-    .value_parser(value_parser!(usize));
-
     let problem = Problem::new(
         "day22",
         "Traverses the path in a grid and find the final position and facing.",
         "Path to the input file. The grid where a . is an empty space, and a # is a wall. Should be a valid cube net.",
-        vec![region_size],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -39,17 +31,17 @@ pub const DAY_22: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(
+        short = 'r',
+        long = "region",
+        value_name = "REGION",
+        help = "The size of the cube faces if folded up into a cube"
+    )]
     cubed_region_size: Option<usize>,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        cubed_region_size: args.get_one::<usize>("region").cloned(),
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Tile {
     Nothing,
@@ -57,7 +49,7 @@ pub enum Tile {
     Wall,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instruction {
     RotateClockwise,
     RotateCounterClockwise,
@@ -87,7 +79,18 @@ fn parse_instruction() -> impl Parser<char, Instruction, Error = Simple<char>> {
 }
 
 fn parse_tiles() -> impl Parser<char, Vec<Vec<Tile>>, Error = Simple<char>> {
-    parse_lines(parse_tile().repeated().at_least(1))
+    parse_lines(parse_tile().repeated().at_least(1)).try_map(|board, span| {
+        if board
+            .first()
+            .iter()
+            .flat_map(|row| row.iter())
+            .any(|tile| tile == &Tile::Space)
+        {
+            Ok(board)
+        } else {
+            Err(Simple::custom(span, "The first row must have an open tile"))
+        }
+    })
 }
 
 fn parse_tile() -> impl Parser<char, Tile, Error = Simple<char>> {
@@ -98,7 +101,7 @@ fn parse_tile() -> impl Parser<char, Tile, Error = Simple<char>> {
     nothing.or(space).or(wall)
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
     let max_x = input.0.iter().map(|row| row.len()).max().unwrap_or(0);
     let max_y = input.0.len();
 
@@ -113,9 +116,10 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
 
     let (point, direction) = match arguments.cubed_region_size {
         Some(region_size) => {
+            validate_cube_net(&board, region_size)?;
             let region_bounds = parse_regions_from_board(&board, region_size);
             let region_path_graph = build_region_path_graph(&region_bounds, region_size);
-            let region_paths_3d = build_3d_region_paths(&region_path_graph);
+            let region_rotation_mappings = fold_cube(&region_path_graph)?;
             traverse_grid_cube(
                 &board,
                 &input.1,
@@ -123,20 +127,80 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
                 max_x - 1,
                 max_y - 1,
                 region_size,
-                &get_region_rotation_mappings(&region_paths_3d),
+                &region_rotation_mappings,
             )
         }
         None => traverse_grid(&board, &input.1, max_x - 1, max_y - 1),
     };
 
-    (point.y + 1) * 1000
+    Ok((point.y + 1) * 1000
         + (point.x + 1) * 4
         + match direction {
             PointDirection::Up => 3,
             PointDirection::Down => 1,
             PointDirection::Left => 2,
             PointDirection::Right => 0,
-        }
+        })
+}
+
+fn validate_cube_net(board: &[Vec<Tile>], region_size: usize) -> Result<()> {
+    let height = board.len();
+    let width = board.first().map(|row| row.len()).unwrap_or(0);
+    if region_size == 0 || !width.is_multiple_of(region_size) || !height.is_multiple_of(region_size)
+    {
+        return Err(anyhow!(
+            "A {}x{} board cannot be split into faces of size {}",
+            width,
+            height,
+            region_size
+        ));
+    }
+
+    let faces = (0..height)
+        .step_by(region_size)
+        .cartesian_product((0..width).step_by(region_size))
+        .filter_map(|(y, x)| {
+            let tiles = board[y..y + region_size]
+                .iter()
+                .flat_map(|row| row[x..x + region_size].iter())
+                .map(|tile| tile == &Tile::Nothing)
+                .unique()
+                .collect::<Vec<_>>();
+            match tiles.as_slice() {
+                [true] => None,
+                [false] => Some(Ok((x, y))),
+                _ => Some(Err(anyhow!("The face at {},{} is partially empty", x, y))),
+            }
+        })
+        .collect::<Result<HashSet<_>>>()?;
+    if faces.len() != 6 {
+        return Err(anyhow!("A cube needs 6 faces, found {}", faces.len()));
+    }
+
+    let mut reached = HashSet::new();
+    bfs(
+        faces.iter().take(1).cloned(),
+        |(x, y)| {
+            [
+                (x + region_size, *y),
+                (x.wrapping_sub(region_size), *y),
+                (*x, y + region_size),
+                (*x, y.wrapping_sub(region_size)),
+            ]
+            .into_iter()
+            .filter(|face| faces.contains(face))
+            .collect::<Vec<_>>()
+        },
+        |face| {
+            reached.insert(*face);
+            false
+        },
+    );
+    if reached.len() != faces.len() {
+        return Err(anyhow!("The faces of the cube are not connected"));
+    }
+
+    Ok(())
 }
 
 fn is_on_region_boundry(
@@ -229,7 +293,7 @@ fn region_direction_mapping(
     region_bounds: &HashMap<usize, (usize, usize)>,
     region_size: usize,
     direction: &PointDirection,
-    region_rotation_mappings: &HashMap<usize, HashMap<PointDirection, (usize, RotationDegrees)>>,
+    region_rotation_mappings: &RegionMappings,
 ) -> (BoundedPoint, PointDirection) {
     let current_region = get_current_region(point, region_bounds, region_size);
     let region_offset = region_size - 1;
@@ -375,149 +439,102 @@ fn region_direction_mapping(
     }
 }
 
-fn insert_mapping(
-    mapping: &mut HashMap<usize, HashMap<PointDirection, (usize, RotationDegrees)>>,
-    from_region: usize,
-    to_region: usize,
-    direction: PointDirection,
-    rotation: RotationDegrees,
-) {
-    let from = mapping.entry(from_region).or_insert(HashMap::new());
-    from.insert(direction.clone(), (to_region, rotation.clone()));
-
-    let to = mapping.entry(to_region).or_insert(HashMap::new());
-    let (new_direction, new_rotation) = match rotation {
-        RotationDegrees::Zero => (direction.get_opposite(), RotationDegrees::Zero),
-        RotationDegrees::Ninety => (
-            direction.get_clockwise(),
-            RotationDegrees::TwoHundredSeventy,
-        ),
-        RotationDegrees::OneHundredEighty => (direction, rotation),
-        RotationDegrees::TwoHundredSeventy => {
-            (direction.get_counter_clockwise(), RotationDegrees::Ninety)
-        }
-    };
-
-    to.insert(new_direction, (from_region, new_rotation));
+#[derive(Debug, Clone, Copy)]
+struct FaceOrientation {
+    normal: Point3<isize>,
+    right: Point3<isize>,
+    down: Point3<isize>,
 }
 
-fn get_mapping_from_path(path: &Vec<PointDirection>) -> Option<(PointDirection, RotationDegrees)> {
-    match path.as_slice() {
-        [p1] => Some((*p1, RotationDegrees::Zero)),
-        [p1, p2] if p1 == p2 => None,
-        [p1, p2] => Some((*p2, p1.get_rotation(p2))),
-        [p1, p2, p3] if p1 == p2 && p2 == p3 => Some((p1.get_opposite(), RotationDegrees::Zero)),
-        [p1, _, p3] if p1 == p3 => None,
-        [p1, p2, p3] if p2 == p3 => Some((p1.get_opposite(), RotationDegrees::OneHundredEighty)),
-        [p1, p2, p3] if p1 == p2 => Some((*p3, RotationDegrees::OneHundredEighty)),
-        [p1, p2, p3, p4] if p1 == p4 && p2 == p3 => None,
-        [p1, p2, p3, p4] if p1 == p2 && p2 == p3 => Some((*p4, p4.get_rotation(p1))),
-        [p1, p2, p3, p4] if p2 == p3 && p3 == p4 => Some((p4.get_opposite(), p4.get_rotation(p1))),
-        [p1, p2, p3, p4] if p1 == p2 && p2 == p4 => Some((p1.get_opposite(), p3.get_rotation(p1))),
-        [p1, p2, p3, p4] if p1 == p3 && p3 == p4 => Some((p2.get_opposite(), p1.get_rotation(p2))),
-        [p1, p2, p3, p4] if p1 == p3 && p2 == p4 => Some((p1.get_opposite(), p2.get_rotation(p1))),
-        [p1, p2, p3, p4, p5] if p1 == p5 && p2 == p3 && p3 == p4 => None,
-        [p1, p2, p3, p4, p5] if p1 == p2 && p2 == p4 && p4 == p5 => {
-            Some((p3.get_opposite(), RotationDegrees::Zero))
-        }
-        [p1, p2, p3, p4, p5] if p1 == p4 && p2 == p3 && p3 == p5 => {
-            Some((p2.get_opposite(), RotationDegrees::Zero))
-        }
-        [p1, p2, p3, p4, p5] if p1 == p3 && p3 == p4 && p2 == p5 => {
-            Some((p1.get_opposite(), RotationDegrees::Zero))
-        }
-        [p1, p2, p3, p4, p5] if p1 == p3 && p3 == p5 && p2 == p4 => {
-            Some((p2.get_opposite(), RotationDegrees::Zero))
+impl FaceOrientation {
+    fn fold(&self, direction: &PointDirection) -> FaceOrientation {
+        match direction {
+            PointDirection::Right => FaceOrientation {
+                normal: self.right,
+                right: -self.normal,
+                ..*self
+            },
+            PointDirection::Left => FaceOrientation {
+                normal: -self.right,
+                right: self.normal,
+                ..*self
+            },
+            PointDirection::Down => FaceOrientation {
+                normal: self.down,
+                down: -self.normal,
+                ..*self
+            },
+            PointDirection::Up => FaceOrientation {
+                normal: -self.down,
+                down: self.normal,
+                ..*self
+            },
         }
-        _ => None,
     }
-}
-
-fn get_region_rotation_mappings(
-    paths: &HashMap<usize, Vec<(usize, Vec<PointDirection>)>>,
-) -> HashMap<usize, HashMap<PointDirection, (usize, RotationDegrees)>> {
-    let mut mappings = HashMap::new();
-
-    let first = paths.get(&1).expect("First face exists");
-    let mut opposite = 1;
-    let mut visitied = HashSet::from([1]);
 
-    first.iter().for_each(|(region, path)| {
-        if let Some((direction, rotation)) = get_mapping_from_path(path) {
-            insert_mapping(&mut mappings, 1, *region, direction, rotation);
-        } else {
-            opposite = *region;
+    fn edge(&self, direction: &PointDirection) -> Point3<isize> {
+        match direction {
+            PointDirection::Right => self.right,
+            PointDirection::Left => -self.right,
+            PointDirection::Down => self.down,
+            PointDirection::Up => -self.down,
         }
-    });
-
-    visitied.insert(opposite);
-
-    let next = paths.get(&opposite).expect("Opposite side exists");
-    next.iter()
-        .filter(|(region, _)| !visitied.contains(region))
-        .for_each(|(region, path)| {
-            if let Some((direction, rotation)) = get_mapping_from_path(path) {
-                insert_mapping(&mut mappings, opposite, *region, direction, rotation);
-            }
-        });
-
-    let next_region = paths
-        .keys()
-        .find(|region| !visitied.contains(region))
-        .expect("At least one unvisited region");
-    visitied.insert(*next_region);
-
-    let next = paths.get(&next_region).expect("Opposite side exists");
-
-    next.iter()
-        .filter(|(region, _)| !visitied.contains(region))
-        .for_each(|(region, path)| {
-            if let Some((direction, rotation)) = get_mapping_from_path(path) {
-                insert_mapping(&mut mappings, *next_region, *region, direction, rotation);
-            } else {
-                opposite = *region
-            }
-        });
-
-    visitied.insert(opposite);
-
-    let next = paths.get(&opposite).expect("Opposite side exists");
-    next.iter()
-        .filter(|(region, _)| !visitied.contains(region))
-        .for_each(|(region, path)| {
-            if let Some((direction, rotation)) = get_mapping_from_path(path) {
-                insert_mapping(&mut mappings, opposite, *region, direction, rotation);
-            }
-        });
-
-    mappings
+    }
 }
 
-fn build_3d_region_paths(
-    paths: &HashMap<usize, Vec<(usize, PointDirection)>>,
-) -> HashMap<usize, Vec<(usize, Vec<PointDirection>)>> {
-    paths
+const DIRECTIONS: [PointDirection; 4] = [
+    PointDirection::Up,
+    PointDirection::Down,
+    PointDirection::Left,
+    PointDirection::Right,
+];
+
+fn fold_cube(graph: &HashMap<usize, Vec<(usize, PointDirection)>>) -> Result<RegionMappings> {
+    let first = FaceOrientation {
+        normal: Point3::new(0, 0, 1),
+        right: Point3::new(1, 0, 0),
+        down: Point3::new(0, 1, 0),
+    };
+    let orientations = graph
         .keys()
         .map(|region| {
-            (
-                *region,
-                paths
-                    .keys()
-                    .filter(|target_region| region != *target_region)
-                    .map(|target_region| {
-                        (
-                            *target_region,
-                            shortest_path(*region, paths, target_region)
-                                .nodes
-                                .into_iter()
-                                .map(|(_, direction)| direction)
-                                .collect(),
-                        )
-                    })
-                    .collect(),
-            )
+            let orientation = region_path_directions(1, graph, region)
+                .iter()
+                .fold(first, |orientation, direction| orientation.fold(direction));
+            (*region, orientation)
         })
-        .collect()
+        .collect::<HashMap<_, _>>();
+
+    if orientations
+        .values()
+        .map(|face| face.normal)
+        .unique()
+        .count()
+        != 6
+    {
+        return Err(anyhow!("The board does not fold into a cube"));
+    }
+
+    Ok(orientations
+        .iter()
+        .map(|(region, face)| {
+            let mapping = DIRECTIONS
+                .iter()
+                .map(|direction| {
+                    let (next_region, next_face) = orientations
+                        .iter()
+                        .find(|(_, next_face)| next_face.normal == face.edge(direction))
+                        .expect("Every edge of a cube has a face");
+                    let arrival = DIRECTIONS
+                        .iter()
+                        .find(|arrival| next_face.edge(arrival) == -face.normal)
+                        .expect("Edges meet at a right angle");
+                    (*direction, (*next_region, arrival.get_rotation(direction)))
+                })
+                .collect();
+            (*region, mapping)
+        })
+        .collect())
 }
 
 fn traverse_grid_cube(
@@ -527,7 +544,7 @@ fn traverse_grid_cube(
     max_x: usize,
     max_y: usize,
     region_size: usize,
-    region_rotation_mappings: &HashMap<usize, HashMap<PointDirection, (usize, RotationDegrees)>>,
+    region_rotation_mappings: &RegionMappings,
 ) -> (BoundedPoint, PointDirection) {
     let mut current_point = board
         .get(0)
@@ -554,7 +571,10 @@ fn traverse_grid_cube(
                 current_direction = current_direction.get_counter_clockwise()
             }
             Instruction::Distance(value) => {
-                for _ in 0..*value {
+                let start = (current_point, current_direction);
+                let mut remaining = *value;
+                let mut walked = 0;
+                while remaining > 0 {
                     if is_on_region_boundry(&current_point, region_size, &current_direction) {
                         let (next_point, next_direction) = region_direction_mapping(
                             &current_point,
@@ -586,6 +606,12 @@ fn traverse_grid_cube(
 
                         current_point = next_point;
                     }
+
+                    remaining -= 1;
+                    walked += 1;
+                    if (current_point, current_direction) == start {
+                        remaining %= walked;
+                    }
                 }
             }
         });
@@ -624,7 +650,10 @@ fn traverse_grid(
                 current_direction = current_direction.get_counter_clockwise()
             }
             Instruction::Distance(value) => {
-                for _ in 0..*value {
+                let start = current_point;
+                let mut remaining = *value;
+                let mut walked = 0;
+                while remaining > 0 {
                     let mut next_point = current_point.get_adjacent_wrapping(&current_direction);
                     let mut next_tile = board
                         .get(next_point.y)
@@ -643,6 +672,12 @@ fn traverse_grid(
                     }
 
                     current_point = next_point;
+                    remaining -= 1;
+                    walked += 1;
+                    // Without a wall in the way every lap ends where the move started.
+                    if current_point == start {
+                        remaining %= walked;
+                    }
                 }
             }
         });
@@ -650,49 +685,70 @@ fn traverse_grid(
     (current_point, current_direction)
 }
 
-#[derive(Debug, Clone)]
-struct Path {
-    nodes: Vec<(usize, PointDirection)>,
-}
-
-fn shortest_path(
+fn region_path_directions(
     start_region: usize,
     graph: &HashMap<usize, Vec<(usize, PointDirection)>>,
     target: &usize,
-) -> Path {
-    let mut visited = BTreeSet::from([start_region]);
-
-    let mut queue = graph
-        .get(&start_region)
-        .iter()
-        .flat_map(|adjacents| adjacents.iter().cloned())
-        .map(|step| Path { nodes: vec![step] })
-        .collect::<VecDeque<Path>>();
-
-    while queue.len() > 0 {
-        let current = queue.pop_front().expect("At least one item in the queue");
-        let last = current.nodes.last().expect("At least one item in the path");
-
-        if &last.0 == target {
-            return current;
-        }
-
-        let filtered_adjacents = graph
-            .get(&last.0)
+) -> Vec<PointDirection> {
+    let adjacents = |region: &usize| {
+        graph
+            .get(region)
             .expect("Valid index")
             .iter()
-            .filter(|node| !visited.contains(&node.0))
-            .cloned()
-            .collect::<Vec<_>>();
+            .map(|(next_region, _)| *next_region)
+            .collect::<Vec<_>>()
+    };
 
-        filtered_adjacents
-            .into_iter()
-            .for_each(|(region, direction)| {
-                let mut new = current.clone();
-                new.nodes.push((region, direction));
-                visited.insert(region);
-                queue.push_back(new);
-            });
+    bfs([start_region], adjacents, |region| region == target)
+        .expect("Regions are connected")
+        .path
+        .windows(2)
+        .map(|step| {
+            graph
+                .get(&step[0])
+                .and_then(|adjacents| {
+                    adjacents
+                        .iter()
+                        .find(|(next_region, _)| *next_region == step[1])
+                })
+                .map(|(_, direction)| *direction)
+                .expect("Step is an edge")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_instruction_reads_each_instruction() {
+        assert_eq!(
+            parse_instruction().parse("10"),
+            Ok(Instruction::Distance(10))
+        );
+        assert_eq!(
+            parse_instruction().parse("R"),
+            Ok(Instruction::RotateClockwise)
+        );
+        assert_eq!(
+            parse_instruction().parse("L"),
+            Ok(Instruction::RotateCounterClockwise)
+        );
+    }
+
+    #[test]
+    fn long_moves_without_walls_wrap_around() {
+        let board = " .\n...\n .\n .\n\n";
+        let score = |path: &str, cubed_region_size| {
+            let input = parse_file(format!("{}{}\n", board, path)).unwrap();
+            run(input, CommandLineArguments { cubed_region_size }).unwrap()
+        };
+
+        assert_eq!(score("18446744073709551615R1", None), score("3R1", None));
+        assert_eq!(
+            score("18446744073709551615R1", Some(1)),
+            score("3R1", Some(1))
+        );
     }
-    unreachable!()
 }
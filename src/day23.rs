@@ -1,13 +1,14 @@
 use adventofcode2022::{
-    flag_arg, parse_lines, single_arg, Command, ParseError, PointDirection, Problem,
+    parse_lines, Command, Grid, ParseError, PointDirection, Problem, RenderArgs, RenderCell,
+    Viewport,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use std::{
     cell::LazyCell,
     collections::{HashMap, HashSet, VecDeque},
@@ -17,25 +18,15 @@ use std::{
 type ParseOutput = Vec<Vec<Tile>>;
 
 pub const DAY_23: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let rounds = single_arg("rounds", 'r', "The number of rounds to iterate for")
-        .value_parser(clap::value_parser!(usize));
-    let equalibrium = flag_arg(
-        "equalibrium",
-        'e',
-        "Runs rounds until the elves do not move, then returns the number of rounds run",
-    )
-    .conflicts_with("rounds");
     let problem = Problem::new(
         "day23",
         "Finds the number of empty ground tiles after the elves have spread out for some iterations or equalibrium",
         "Path to the input file. The initial positions of the elves.",
-        vec![rounds, equalibrium],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { iteration_limit: IterationLimit::Rounds(10) }, "The number of empty spaces after 10 rounds.")
-    .with_part2(CommandLineArguments { iteration_limit: IterationLimit::Equalibrium }, "The number of rounds until equalibrium is reached.");
+    .with_part1(CommandLineArguments { iteration_limit: IterationLimitArgs { rounds: Some(10), equalibrium: false }, render: RenderArgs::default() }, "The number of empty spaces after 10 rounds.")
+    .with_part2(CommandLineArguments { iteration_limit: IterationLimitArgs { rounds: None, equalibrium: true }, render: RenderArgs::default() }, "The number of rounds until equalibrium is reached.");
     Box::new(problem)
 });
 
@@ -45,28 +36,56 @@ pub enum IterationLimit {
     Equalibrium,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
-    iteration_limit: IterationLimit,
+    #[command(flatten)]
+    iteration_limit: IterationLimitArgs,
+    #[command(flatten)]
+    render: RenderArgs,
 }
 
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    let rounds = args.get_one::<usize>("rounds");
-    let equalibrium = args.get_one::<bool>("equalibrium");
-    let iteration_limit = match (rounds, equalibrium) {
-        (None, Some(true)) => IterationLimit::Equalibrium,
-        (Some(value), Some(false)) => IterationLimit::Rounds(*value),
-        _ => unreachable!(),
-    };
-    CommandLineArguments { iteration_limit }
+#[derive(Debug, Clone, Args)]
+#[group(multiple = false, required = true)]
+pub struct IterationLimitArgs {
+    #[arg(short, long, help = "The number of rounds to iterate for")]
+    rounds: Option<usize>,
+    #[arg(
+        short,
+        long,
+        help = "Runs rounds until the elves do not move, then returns the number of rounds run"
+    )]
+    equalibrium: bool,
 }
 
-#[derive(Debug, Clone)]
+impl TryFrom<IterationLimitArgs> for IterationLimit {
+    type Error = anyhow::Error;
+
+    fn try_from(args: IterationLimitArgs) -> Result<Self> {
+        match (args.rounds, args.equalibrium) {
+            (Some(rounds), false) => Ok(IterationLimit::Rounds(rounds)),
+            (None, true) => Ok(IterationLimit::Equalibrium),
+            _ => Err(anyhow!(
+                "Exactly one of a round count or equalibrium is required"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Tile {
     Elf,
     Empty,
 }
 
+impl RenderCell for Tile {
+    fn to_char(&self) -> char {
+        match self {
+            Tile::Elf => '#',
+            Tile::Empty => '.',
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Point {
     x: isize,
@@ -89,7 +108,9 @@ fn parse_tile() -> impl Parser<char, Tile, Error = Simple<char>> {
     elf.or(empty)
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    let render = arguments.render.options();
+    let iteration_limit = IterationLimit::try_from(arguments.iteration_limit)?;
     let mut elf_points = input
         .into_iter()
         .enumerate()
@@ -124,7 +145,13 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
         let front = directions.pop_front().expect("Direction exists");
         directions.push_back(front);
 
-        match arguments.iteration_limit {
+        if let Some(render) = &render {
+            if render.is_render_step(count) {
+                render.print(&format!("After round {}", count), &Elves(&elf_points));
+            }
+        }
+
+        match iteration_limit {
             IterationLimit::Rounds(limit) => {
                 if count == limit {
                     break;
@@ -140,17 +167,23 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
         };
     }
 
-    match arguments.iteration_limit {
+    if let Some(render) = &render {
+        if !render.is_render_step(count) {
+            render.print(&format!("After round {}", count), &Elves(&elf_points));
+        }
+        render.finish()?;
+    }
+
+    Ok(match iteration_limit {
         IterationLimit::Rounds(_) => {
-            let max_x = elf_points.iter().map(|point| point.x).max().unwrap_or(0);
-            let min_x = elf_points.iter().map(|point| point.x).min().unwrap_or(0);
-            let max_y = elf_points.iter().map(|point| point.y).max().unwrap_or(0);
-            let min_y = elf_points.iter().map(|point| point.y).min().unwrap_or(0);
+            let viewport = Elves(&elf_points).viewport();
 
-            (max_x - min_x + 1) as usize * (max_y - min_y + 1) as usize - elf_points.len()
+            (viewport.max_x - viewport.min_x + 1) as usize
+                * (viewport.max_y - viewport.min_y + 1) as usize
+                - elf_points.len()
         }
         IterationLimit::Equalibrium => count,
-    }
+    })
 }
 
 fn run_iteration(
@@ -264,20 +297,35 @@ fn run_iteration(
         .collect()
 }
 
-fn _print_elves(elves: &HashSet<Point>) {
-    let max_x = elves.iter().map(|point| point.x).max().unwrap_or(0);
-    let min_x = elves.iter().map(|point| point.x).min().unwrap_or(0);
-    let max_y = elves.iter().map(|point| point.y).max().unwrap_or(0);
-    let min_y = elves.iter().map(|point| point.y).min().unwrap_or(0);
+struct Elves<'a>(&'a HashSet<Point>);
 
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            if elves.contains(&Point { x, y }) {
-                print!("#")
-            } else {
-                print!(".")
-            }
+impl Grid for Elves<'_> {
+    type Cell = Tile;
+
+    fn viewport(&self) -> Viewport {
+        Viewport::from_points(self.0.iter().map(|point| (point.x, point.y))).unwrap_or(Viewport {
+            min_x: 0,
+            min_y: 0,
+            max_x: 0,
+            max_y: 0,
+        })
+    }
+
+    fn cell(&self, x: isize, y: isize) -> Tile {
+        if self.0.contains(&Point { x, y }) {
+            Tile::Elf
+        } else {
+            Tile::Empty
         }
-        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_rejects_unknown_tiles() {
+        assert!(parse_file("#.#\n.E.".to_string()).is_err());
     }
 }
@@ -1,53 +1,43 @@
 use adventofcode2022::{
-    parse_lines, single_arg, BoundedPoint, Command, ParseError, PointDirection, Problem,
+    bfs, parse_grid, BoundedPoint, Command, Grid, ParseError, PointDirection, Problem, RenderArgs,
+    RenderCell, Viewport,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use ariadne::Color;
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use std::{
     cell::LazyCell,
-    collections::{BTreeMap, HashSet, VecDeque},
+    collections::{BTreeMap, VecDeque},
     iter::once,
 };
+use tracing::debug;
 
 type ParseOutput = Vec<Vec<Tile>>;
 
 pub const DAY_24: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
-    let traversals = single_arg(
-        "traversals",
-        't',
-        "The number of times to traverse the snow storm",
-    )
-    .value_parser(clap::value_parser!(usize));
     let problem = Problem::new(
         "day24",
         "Figures out how long it will take the elves to navigatea snow storm.",
         "Path to the input file. The current status of the field with the direction blizzards are traveling.",
-        vec![traversals],
-        parse_arguments,
         parse_file,
         run,
     )
-    .with_part1(CommandLineArguments { traversals: 1 }, "Finds how long it takes to traverse the snow storm")
-    .with_part2(CommandLineArguments { traversals: 3 }, "Finds how long it takes to traverse the snow storm 3 times.");
+    .with_part1(CommandLineArguments { traversals: 1, render: RenderArgs::default() }, "Finds how long it takes to traverse the snow storm")
+    .with_part2(CommandLineArguments { traversals: 3, render: RenderArgs::default() }, "Finds how long it takes to traverse the snow storm 3 times.");
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {
+    #[arg(short, long, help = "The number of times to traverse the snow storm")]
     traversals: usize,
-}
-
-fn parse_arguments(args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {
-        traversals: *args
-            .get_one::<usize>("traversals")
-            .expect("Valid arguments"),
-    }
+    #[command(flatten)]
+    render: RenderArgs,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -65,7 +55,39 @@ fn parse_file(file: String) -> Result<ParseOutput> {
 }
 
 fn parser() -> impl Parser<char, ParseOutput, Error = Simple<char>> {
-    parse_lines(parse_tile().repeated().at_least(1)).then_ignore(end())
+    parse_grid(parse_tile())
+        .then_ignore(end())
+        .try_map(|valley, span| {
+            validate_valley(&valley).map_err(|message| Simple::custom(span, message))?;
+            Ok(valley)
+        })
+}
+
+fn validate_valley(valley: &[Vec<Tile>]) -> Result<(), String> {
+    let height = valley.len();
+    let width = valley.first().map(|row| row.len()).unwrap_or(0);
+    if height < 3 || width < 3 {
+        return Err("The valley must be surrounded by walls".to_string());
+    }
+
+    if [&valley[0], &valley[height - 1]].into_iter().any(|row| {
+        row.iter().filter(|tile| **tile == Tile::Empty).count() != 1
+            || row
+                .iter()
+                .any(|tile| *tile != Tile::Empty && *tile != Tile::Wall)
+    }) {
+        return Err("The top and bottom walls must have exactly one opening".to_string());
+    }
+
+    if valley[1..height - 1].iter().any(|row| {
+        row[0] != Tile::Wall
+            || row[width - 1] != Tile::Wall
+            || row[1..width - 1].iter().any(|tile| tile == &Tile::Wall)
+    }) {
+        return Err("The valley must only have walls on its edges".to_string());
+    }
+
+    Ok(())
 }
 
 fn parse_tile() -> impl Parser<char, Tile, Error = Simple<char>> {
@@ -86,7 +108,8 @@ fn parse_direction() -> impl Parser<char, PointDirection, Error = Simple<char>>
     left.or(right).or(down).or(up)
 }
 
-fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
+fn run(input: ParseOutput, arguments: CommandLineArguments) -> Result<usize> {
+    let render = arguments.render.options();
     let max_x = input.iter().map(|row| row.len() - 1).max().unwrap_or(0);
     let max_y = input.len() - 1;
 
@@ -154,15 +177,32 @@ fn run(input: ParseOutput, arguments: CommandLineArguments) -> usize {
 
     all_maps.rotate_left(all_maps.len() - 1);
 
-    println!("Found {} possible storm patterns", all_maps.len());
+    debug!(patterns = all_maps.len(), "Found storm patterns");
 
     let mut count = 0;
+    let mut route = Vec::new();
     for _ in 0..arguments.traversals {
-        count = find_path(start_point, target_point, count, &all_maps);
+        let (next_count, path) = find_path(start_point, target_point, count, &all_maps)
+            .ok_or_else(|| anyhow!("The expedition cannot reach the other side"))?;
+        route.extend(path.into_iter().skip(1).map(|(point, _)| point));
+        count = next_count;
         (start_point, target_point) = (target_point, start_point);
     }
 
-    count - 1
+    if let Some(render) = &render {
+        route.iter().enumerate().for_each(|(minute, expedition)| {
+            if render.is_render_step(minute) || minute == route.len() - 1 {
+                let valley = Valley {
+                    map: all_maps.get(minute % all_maps.len()).expect("Index exists"),
+                    expedition: *expedition,
+                };
+                render.print(&format!("Minute {}", minute), &valley);
+            }
+        });
+        render.finish()?;
+    }
+
+    Ok(count - 1)
 }
 
 fn find_path(
@@ -170,27 +210,26 @@ fn find_path(
     target_point: BoundedPoint,
     count: usize,
     all_maps: &VecDeque<BTreeMap<BoundedPoint, Vec<Tile>>>,
-) -> usize {
-    let mut queue = VecDeque::from([(start_point, count)]);
-    let mut cache = HashSet::new();
-
-    while queue.len() > 0 {
-        let (current_expedition, movement) = queue.pop_front().expect("Queue is not empty");
-        if current_expedition == target_point {
-            return movement;
-        }
+) -> Option<(usize, Vec<(BoundedPoint, usize)>)> {
+    let period = all_maps.len();
+    let result = bfs(
+        [(start_point, count % period)],
+        |(expedition, phase)| {
+            valid_expedition_movements(expedition, *phase, all_maps)
+                .into_iter()
+                .map(|(next, movement)| (next, movement % period))
+        },
+        |(expedition, _)| *expedition == target_point,
+    )?;
+    debug!(
+        start = count,
+        minutes = result.cost,
+        visited = result.stats.visited,
+        max_frontier = result.stats.max_frontier,
+        "Crossed the valley"
+    );
 
-        valid_expedition_movements(&current_expedition, movement, all_maps)
-            .into_iter()
-            .for_each(|next| {
-                if !cache.contains(&(next.0, next.1 % all_maps.len())) {
-                    queue.push_back(next.clone());
-                    cache.insert((next.0, next.1 % all_maps.len()));
-                }
-            });
-    }
-
-    unreachable!()
+    Some((count + result.cost, result.path))
 }
 
 fn valid_expedition_movements(
@@ -248,28 +287,82 @@ fn run_movements(map: BTreeMap<BoundedPoint, Vec<Tile>>) -> BTreeMap<BoundedPoin
         )
 }
 
-fn _print_map(map: &BTreeMap<BoundedPoint, Vec<Tile>>, max_x: usize, max_y: usize) {
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            let fallback = vec![Tile::Empty];
-            let point = map
-                .get(&BoundedPoint { x, y, max_x, max_y })
-                .unwrap_or(&fallback);
-            match &point[..] {
-                [tile] => match tile {
-                    Tile::Wall => print!("#"),
-                    Tile::Blizzard(direction) => match direction {
-                        PointDirection::Up => print!("^"),
-                        PointDirection::Down => print!("v"),
-                        PointDirection::Left => print!("<"),
-                        PointDirection::Right => print!(">"),
-                    },
-                    Tile::Empty => print!("."),
-                    Tile::Expedition => print!("E"),
+struct Valley<'a> {
+    map: &'a BTreeMap<BoundedPoint, Vec<Tile>>,
+    expedition: BoundedPoint,
+}
+
+impl Grid for Valley<'_> {
+    type Cell = ValleySpace;
+
+    fn viewport(&self) -> Viewport {
+        Viewport {
+            min_x: 0,
+            min_y: 0,
+            max_x: self.expedition.max_x as isize,
+            max_y: self.expedition.max_y as isize,
+        }
+    }
+
+    fn cell(&self, x: isize, y: isize) -> ValleySpace {
+        let point = BoundedPoint {
+            x: x as usize,
+            y: y as usize,
+            max_x: self.expedition.max_x,
+            max_y: self.expedition.max_y,
+        };
+        if point == self.expedition {
+            ValleySpace(vec![Tile::Expedition])
+        } else {
+            ValleySpace(self.map.get(&point).cloned().unwrap_or_default())
+        }
+    }
+}
+
+struct ValleySpace(Vec<Tile>);
+
+impl RenderCell for ValleySpace {
+    fn to_char(&self) -> char {
+        match &self.0[..] {
+            [] => '.',
+            [tile] => match tile {
+                Tile::Wall => '#',
+                Tile::Blizzard(direction) => match direction {
+                    PointDirection::Up => '^',
+                    PointDirection::Down => 'v',
+                    PointDirection::Left => '<',
+                    PointDirection::Right => '>',
                 },
-                _ => print!("{}", point.len()),
-            }
+                Tile::Empty => '.',
+                Tile::Expedition => 'E',
+            },
+            tiles => char::from_digit(tiles.len() as u32, 10).unwrap_or('*'),
         }
-        println!()
+    }
+
+    fn color(&self) -> Option<Color> {
+        match &self.0[..] {
+            [Tile::Expedition] => Some(Color::Green),
+            [Tile::Wall] | [Tile::Empty] | [] => None,
+            _ => Some(Color::Cyan),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tile_reads_blizzards() {
+        assert_eq!(
+            parse_tile().parse(">"),
+            Ok(Tile::Blizzard(PointDirection::Right))
+        );
+        assert_eq!(
+            parse_tile().parse("^"),
+            Ok(Tile::Blizzard(PointDirection::Up))
+        );
+        assert_eq!(parse_tile().parse("#"), Ok(Tile::Wall));
     }
 }
@@ -1,11 +1,11 @@
 use adventofcode2022::{parse_lines, Command, ParseError, Problem};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chumsky::{
     prelude::Simple,
     primitive::{end, just},
     Parser,
 };
-use clap::ArgMatches;
+use clap::Args;
 use itertools::Itertools;
 use std::{cell::LazyCell, fmt::Display};
 
@@ -16,8 +16,6 @@ pub const DAY_25: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
         "day25",
         "Sums snafu numbers",
         "Path to the input file. The snafu numbers to sum, one snafu number on each line.",
-        vec![],
-        parse_arguments,
         parse_file,
         run,
     )
@@ -28,14 +26,10 @@ pub const DAY_25: LazyCell<Box<dyn Command>> = LazyCell::new(|| {
     Box::new(problem)
 });
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Args)]
 pub struct CommandLineArguments {}
 
-fn parse_arguments(_args: &ArgMatches) -> CommandLineArguments {
-    CommandLineArguments {}
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Snafu {
     Two,
     One,
@@ -70,19 +64,21 @@ fn parse_snafu() -> impl Parser<char, Snafu, Error = Simple<char>> {
     two.or(one).or(zero).or(minus).or(double_minus)
 }
 
-fn run(input: ParseOutput, _arguments: CommandLineArguments) -> String {
-    let result: SnafuNumber = input
+fn run(input: ParseOutput, _arguments: CommandLineArguments) -> Result<String> {
+    let total = input
         .into_iter()
-        .map(|snafu| {
-            let integer: isize = snafu.into();
-            integer
-        })
-        .sum::<isize>()
-        .into();
-
-    result.to_string()
+        .enumerate()
+        .try_fold(0isize, |total, (index, snafu)| {
+            isize::try_from(snafu)
+                .ok()
+                .and_then(|integer| total.checked_add(integer))
+                .ok_or_else(|| anyhow!("Line {} brings the fuel total out of range", index + 1))
+        })?;
+
+    Ok(SnafuNumber::from(total).to_string())
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct SnafuNumber(Vec<Snafu>);
 
 impl Display for SnafuNumber {
@@ -103,23 +99,24 @@ impl Display for SnafuNumber {
     }
 }
 
-impl Into<isize> for SnafuNumber {
-    fn into(self) -> isize {
-        let length = self.0.len();
-        self.0
+impl TryFrom<SnafuNumber> for isize {
+    type Error = anyhow::Error;
+
+    fn try_from(snafu: SnafuNumber) -> Result<Self> {
+        snafu
+            .0
             .iter()
-            .enumerate()
-            .map(|(index, value)| {
-                5_isize.pow((length - index - 1) as u32)
-                    * match value {
-                        Snafu::Two => 2,
-                        Snafu::One => 1,
-                        Snafu::Zero => 0,
-                        Snafu::Minus => -1,
-                        Snafu::DoubleMinus => -2,
-                    }
+            .try_fold(0isize, |total, value| {
+                let digit = match value {
+                    Snafu::Two => 2,
+                    Snafu::One => 1,
+                    Snafu::Zero => 0,
+                    Snafu::Minus => -1,
+                    Snafu::DoubleMinus => -2,
+                };
+                total.checked_mul(5)?.checked_add(digit)
             })
-            .sum::<isize>()
+            .ok_or_else(|| anyhow!("{} does not fit in an integer", snafu))
     }
 }
 
@@ -128,7 +125,7 @@ impl From<isize> for SnafuNumber {
         let mut number = value;
         let mut snafu = Vec::new();
         let mut carry = 0;
-        while number > 0 {
+        while number > 0 || carry > 0 {
             let remainder = number % 5 + carry;
             number = number / 5;
 
@@ -164,8 +161,54 @@ impl From<isize> for SnafuNumber {
             }
         }
 
+        if snafu.is_empty() {
+            snafu.push(Snafu::Zero);
+        }
+
         snafu.reverse();
 
         SnafuNumber(snafu)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snafu_numbers_round_trip_through_integers() {
+        (0isize..=2022).for_each(|value| {
+            let snafu: SnafuNumber = value.into();
+            let text = snafu.to_string();
+            let parsed = parse_file(text.clone())
+                .expect("Snafu parses")
+                .pop()
+                .expect("One number");
+            let integer = isize::try_from(parsed).unwrap();
+
+            assert_eq!(integer, value, "{} did not round trip", text);
+        });
+    }
+
+    #[test]
+    fn snafu_numbers_match_known_values() {
+        [
+            (1747isize, "1=-0-2"),
+            (906, "12111"),
+            (198, "2=0="),
+            (4890, "2=-1=0"),
+        ]
+        .into_iter()
+        .for_each(|(value, expected)| {
+            let snafu: SnafuNumber = value.into();
+            assert_eq!(snafu.to_string(), expected);
+        });
+    }
+
+    #[test]
+    fn oversized_numbers_are_an_error() {
+        let input = parse_file("2=".repeat(30)).unwrap();
+
+        assert!(run(input, CommandLineArguments {}).is_err());
+    }
+}
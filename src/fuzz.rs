@@ -0,0 +1,207 @@
+use crate::{commands, random::Rng};
+use itertools::Itertools;
+use std::{
+    env,
+    iter::once,
+    sync::mpsc::{channel, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+const TARGETS: &[(&str, &[&str])] = &[
+    ("day01", &["-n", "3"]),
+    ("day02", &["-s", "outcome"]),
+    ("day03", &["-g", "3"]),
+    ("day04", &["-o", "any"]),
+    ("day05", &["-u"]),
+    ("day06", &["-n", "4"]),
+    ("day07", &["-s", "30000000"]),
+    ("day08", &["-s", "best-tree"]),
+    ("day09", &["-l", "10"]),
+    ("day10", &["-c", "20,60"]),
+    ("day11", &["-r", "20", "-c", "3"]),
+    ("day12", &["-e"]),
+    ("day13", &["-o", "global"]),
+    ("day14", &["-b"]),
+    ("day15", &["-a", "20"]),
+    ("day16", &["-t", "10", "-e", "2"]),
+    ("day17", &["-n", "2022"]),
+    ("day18", &["-a"]),
+    ("day19", &["-t", "8", "-s", "quality-level-sum"]),
+    ("day20", &["-i", "2", "-k", "811589153"]),
+    ("day21", &["-e"]),
+    ("day22", &["-r", "4"]),
+    ("day23", &["-r", "10"]),
+    ("day24", &["-t", "3"]),
+    ("day25", &[]),
+];
+
+// Inputs that once crashed a day, run as they are and used as extra bases for mutation.
+const SEEDS: &[(&str, &[&str], &str)] = &[
+    ("day04", &["-r"], "0-18446744073709551615,2-3\n"),
+    (
+        "day07",
+        &["-s", "30000000"],
+        "$ cd /\n$ ls\n18446744073709551615 a\n1 b\n",
+    ),
+    (
+        "day10",
+        &["-c", "20,60"],
+        "addx 9223372036854775807\naddx 5\n",
+    ),
+    (
+        "day15",
+        &["-l", "0"],
+        "Sensor at x=9000000000000000000, y=0: closest beacon is at x=-9000000000000000000, y=0\n",
+    ),
+    (
+        "day15",
+        &["-a", "20"],
+        "Sensor at x=4000000000000000000, y=0: closest beacon is at x=0, y=4000000000000000000\n",
+    ),
+    (
+        "day22",
+        &["-r", "1"],
+        " .\n...\n .\n .\n\n18446744073709551615R1\n",
+    ),
+    (
+        "day25",
+        &[],
+        "2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=2=\n",
+    ),
+];
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const OVERFLOW_DIGITS: usize = 20;
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+enum Outcome {
+    Finished,
+    Panicked,
+    TimedOut,
+}
+
+fn mutate(rng: &mut Rng, input: &str) -> String {
+    let alphabet = input
+        .chars()
+        .chain("\n -09".chars())
+        .unique()
+        .collect::<Vec<_>>();
+    let mut chars = input.chars().collect::<Vec<_>>();
+
+    (0..rng.usize_in(1..=4)).for_each(|_| {
+        let position = rng.usize_in(0..=chars.len());
+        match rng.usize_in(0..=5) {
+            0 if position < chars.len() => chars[position] = *rng.choose(&alphabet),
+            1 => chars.insert(position, *rng.choose(&alphabet)),
+            2 if position < chars.len() => {
+                chars.remove(position);
+            }
+            3 => (0..rng.usize_in(1..=OVERFLOW_DIGITS)).for_each(|_| chars.insert(position, '9')),
+            4 => {
+                let lines = chars.split(|c| *c == '\n').collect::<Vec<_>>();
+                let line = lines[rng.usize_in(0..=lines.len() - 1)].to_vec();
+                let mut result = lines.iter().map(|line| line.to_vec()).collect::<Vec<_>>();
+                if rng.bool() {
+                    result.insert(rng.usize_in(0..=result.len()), line);
+                } else {
+                    result.remove(rng.usize_in(0..=result.len() - 1));
+                }
+                chars = result.join(&'\n');
+            }
+            _ => chars.truncate(position),
+        }
+    });
+
+    chars.into_iter().collect()
+}
+
+fn run_case(day: &'static str, args: &'static [&'static str], input: String) -> Outcome {
+    let (sender, receiver) = channel();
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let (_, command) = commands()
+                .into_iter()
+                .find(|(name, _)| *name == day)
+                .expect("Day is registered");
+            let matches = command
+                .get_subcommand()
+                .try_get_matches_from([day, "-f", "fuzz"].into_iter().chain(args.iter().copied()))
+                .expect("Valid arguments");
+            let _ = command.run_input(input, &matches);
+            let _ = sender.send(());
+        })
+        .expect("Thread spawns");
+
+    match receiver.recv_timeout(TIMEOUT) {
+        Ok(()) => Outcome::Finished,
+        Err(RecvTimeoutError::Disconnected) => Outcome::Panicked,
+        Err(RecvTimeoutError::Timeout) => Outcome::TimedOut,
+    }
+}
+
+fn failure(day: &str, case: &str, input: &str, outcome: Outcome) -> Option<String> {
+    match outcome {
+        Outcome::Finished => None,
+        Outcome::Panicked => Some(format!("{} {} panicked on {:?}", day, case, input)),
+        Outcome::TimedOut => Some(format!("{} {} timed out on {:?}", day, case, input)),
+    }
+}
+
+fn fuzz(cases: usize) {
+    let day_filter = env::var("FUZZ_DAY").ok();
+    let cases = env::var("FUZZ_CASES")
+        .ok()
+        .and_then(|cases| cases.parse().ok())
+        .unwrap_or(cases);
+    let failures = TARGETS
+        .iter()
+        .filter(|(day, _)| day_filter.iter().all(|filter| filter == day))
+        .flat_map(|(day, args)| {
+            let sample =
+                std::fs::read_to_string(format!("{}/sample.txt", day)).expect("Sample exists");
+            let bases = once(sample)
+                .chain(
+                    SEEDS
+                        .iter()
+                        .filter(|(seed_day, _, _)| seed_day == day)
+                        .map(|(_, _, input)| input.to_string()),
+                )
+                .collect::<Vec<_>>();
+            (0..cases).filter_map(move |case| {
+                let mut rng = Rng::new(case as u64);
+                let base = rng.choose(&bases).clone();
+                let input = mutate(&mut rng, &base);
+                let outcome = run_case(day, args, input.clone());
+                failure(day, &format!("case {}", case), &input, outcome)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn seed_corpus_never_panics() {
+    let failures = SEEDS
+        .iter()
+        .filter_map(|(day, args, input)| {
+            let outcome = run_case(day, args, input.to_string());
+            failure(day, "seed", input, outcome)
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn parse_then_run_never_panics() {
+    fuzz(50);
+}
+
+#[test]
+#[ignore = "long running fuzz session, run with --ignored"]
+fn parse_then_run_never_panics_extended() {
+    fuzz(2000);
+}
@@ -0,0 +1,428 @@
+use clap::ValueEnum;
+use itertools::Itertools;
+use std::{
+    cmp::{max, min, Ordering},
+    ops::{Add, Sub},
+};
+
+pub trait CheckedAdd: Sized {
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+pub trait CheckedSub: Sized {
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_ops {
+    ($($t:ty),*) => {
+        $(impl CheckedAdd for $t {
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+        }
+
+        impl CheckedSub for $t {
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *other)
+            }
+        })*
+    };
+}
+
+impl_checked_ops!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Interval<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + CheckedAdd + CheckedSub,
+{
+    pub fn new(start: T, end: T) -> Interval<T> {
+        Interval { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    pub fn length(&self) -> Option<T> {
+        if self.is_empty() {
+            Some(T::from(0))
+        } else {
+            self.end.checked_sub(&self.start)?.checked_add(&T::from(1))
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        &self.start <= value && value <= &self.end
+    }
+
+    pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let intersection = Interval {
+            start: max(self.start, other.start),
+            end: min(self.end, other.end),
+        };
+        if intersection.is_empty() {
+            None
+        } else {
+            Some(intersection)
+        }
+    }
+
+    pub fn relation(&self, other: &Interval<T>) -> Option<IntervalRelation> {
+        if self.is_empty() || other.is_empty() {
+            return None;
+        }
+        let before = |first: &Interval<T>, second: &Interval<T>| {
+            first.after_end().map(|after| after.cmp(&second.start))
+        };
+
+        let relation = if before(self, other) == Some(Ordering::Less) {
+            IntervalRelation::Before
+        } else if before(self, other) == Some(Ordering::Equal) {
+            IntervalRelation::Meets
+        } else if before(other, self) == Some(Ordering::Less) {
+            IntervalRelation::After
+        } else if before(other, self) == Some(Ordering::Equal) {
+            IntervalRelation::MetBy
+        } else {
+            match (self.start.cmp(&other.start), self.end.cmp(&other.end)) {
+                (Ordering::Equal, Ordering::Equal) => IntervalRelation::Equals,
+                (Ordering::Equal, Ordering::Less) => IntervalRelation::Starts,
+                (Ordering::Equal, Ordering::Greater) => IntervalRelation::StartedBy,
+                (Ordering::Greater, Ordering::Equal) => IntervalRelation::Finishes,
+                (Ordering::Less, Ordering::Equal) => IntervalRelation::FinishedBy,
+                (Ordering::Greater, Ordering::Less) => IntervalRelation::During,
+                (Ordering::Less, Ordering::Greater) => IntervalRelation::Contains,
+                (Ordering::Less, Ordering::Less) => IntervalRelation::Overlaps,
+                (Ordering::Greater, Ordering::Greater) => IntervalRelation::OverlappedBy,
+            }
+        };
+
+        Some(relation)
+    }
+
+    fn after_end(&self) -> Option<T> {
+        self.end.checked_add(&T::from(1))
+    }
+
+    fn touches(&self, other: &Interval<T>) -> bool {
+        self.after_end().is_none_or(|after| other.start <= after)
+            && other.after_end().is_none_or(|after| self.start <= after)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub enum IntervalRelation {
+    Before,
+    Meets,
+    Overlaps,
+    Starts,
+    During,
+    Finishes,
+    Equals,
+    FinishedBy,
+    Contains,
+    StartedBy,
+    OverlappedBy,
+    MetBy,
+    After,
+}
+
+impl IntervalRelation {
+    pub const ALL: [IntervalRelation; 13] = [
+        IntervalRelation::Before,
+        IntervalRelation::Meets,
+        IntervalRelation::Overlaps,
+        IntervalRelation::Starts,
+        IntervalRelation::During,
+        IntervalRelation::Finishes,
+        IntervalRelation::Equals,
+        IntervalRelation::FinishedBy,
+        IntervalRelation::Contains,
+        IntervalRelation::StartedBy,
+        IntervalRelation::OverlappedBy,
+        IntervalRelation::MetBy,
+        IntervalRelation::After,
+    ];
+
+    pub fn inverse(&self) -> IntervalRelation {
+        match self {
+            IntervalRelation::Before => IntervalRelation::After,
+            IntervalRelation::Meets => IntervalRelation::MetBy,
+            IntervalRelation::Overlaps => IntervalRelation::OverlappedBy,
+            IntervalRelation::Starts => IntervalRelation::StartedBy,
+            IntervalRelation::During => IntervalRelation::Contains,
+            IntervalRelation::Finishes => IntervalRelation::FinishedBy,
+            IntervalRelation::Equals => IntervalRelation::Equals,
+            IntervalRelation::FinishedBy => IntervalRelation::Finishes,
+            IntervalRelation::Contains => IntervalRelation::During,
+            IntervalRelation::StartedBy => IntervalRelation::Starts,
+            IntervalRelation::OverlappedBy => IntervalRelation::Overlaps,
+            IntervalRelation::MetBy => IntervalRelation::Meets,
+            IntervalRelation::After => IntervalRelation::Before,
+        }
+    }
+
+    pub fn shares_values(&self) -> bool {
+        !matches!(
+            self,
+            IntervalRelation::Before
+                | IntervalRelation::Meets
+                | IntervalRelation::MetBy
+                | IntervalRelation::After
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + CheckedAdd + CheckedSub,
+{
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet::default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn length(&self) -> Option<T> {
+        self.intervals.iter().try_fold(T::from(0), |acc, interval| {
+            acc.checked_add(&interval.length()?)
+        })
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.intervals
+            .iter()
+            .any(|interval| interval.contains(value))
+    }
+
+    pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+        other.is_empty()
+            || self
+                .intervals
+                .iter()
+                .any(|interval| interval.contains_interval(other))
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+
+        let (touching, mut rest): (Vec<_>, Vec<_>) = std::mem::take(&mut self.intervals)
+            .into_iter()
+            .partition(|existing| existing.touches(&interval));
+        let merged = touching
+            .into_iter()
+            .fold(interval, |acc, existing| Interval {
+                start: min(acc.start, existing.start),
+                end: max(acc.end, existing.end),
+            });
+        let position = rest
+            .iter()
+            .position(|existing| existing.start > merged.start)
+            .unwrap_or(rest.len());
+        rest.insert(position, merged);
+        self.intervals = rest;
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    pub fn intersect(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.iter()
+            .flat_map(|interval| {
+                other
+                    .iter()
+                    .filter_map(move |other_interval| interval.intersection(other_interval))
+            })
+            .collect()
+    }
+
+    pub fn subtract(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.iter()
+            .flat_map(|interval| {
+                let mut pieces = Vec::new();
+                let mut start = interval.start;
+                let mut remaining = true;
+
+                for cut in other
+                    .iter()
+                    .filter(|cut| cut.intersection(interval).is_some())
+                {
+                    if cut.start > start {
+                        pieces.push(Interval::new(start, cut.start - T::from(1)));
+                    }
+                    if cut.end >= interval.end {
+                        remaining = false;
+                        break;
+                    }
+                    start = cut.end + T::from(1);
+                }
+
+                if remaining {
+                    pieces.push(Interval::new(start, interval.end));
+                }
+                pieces
+            })
+            .collect()
+    }
+
+    pub fn gaps(&self) -> impl Iterator<Item = Interval<T>> + '_ {
+        self.intervals
+            .iter()
+            .tuple_windows()
+            .map(|(previous, next)| Interval {
+                start: previous.end + T::from(1),
+                end: next.start - T::from(1),
+            })
+    }
+}
+
+impl<T> From<Interval<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + CheckedAdd + CheckedSub,
+{
+    fn from(interval: Interval<T>) -> Self {
+        IntervalSet::from_iter([interval])
+    }
+}
+
+impl<T> FromIterator<Interval<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + CheckedAdd + CheckedSub,
+{
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let intervals = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .sorted_by(|a, b| a.start.cmp(&b.start))
+            .fold(Vec::<Interval<T>>::new(), |mut acc, interval| {
+                match acc.last_mut() {
+                    Some(last) if last.touches(&interval) => last.end = max(last.end, interval.end),
+                    _ => acc.push(interval),
+                }
+                acc
+            });
+        IntervalSet { intervals }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(usize, usize)]) -> IntervalSet<usize> {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    fn intervals(set: &IntervalSet<usize>) -> Vec<(usize, usize)> {
+        set.iter()
+            .map(|interval| (interval.start, interval.end))
+            .collect()
+    }
+
+    #[test]
+    fn insert_merges_touching_intervals() {
+        let mut intervals_set = set(&[(1, 3), (10, 12)]);
+        intervals_set.insert(Interval::new(4, 5));
+        assert_eq!(intervals(&intervals_set), vec![(1, 5), (10, 12)]);
+
+        intervals_set.insert(Interval::new(6, 9));
+        assert_eq!(intervals(&intervals_set), vec![(1, 12)]);
+
+        intervals_set.insert(Interval::new(20, 19));
+        intervals_set.insert(Interval::new(14, 15));
+        assert_eq!(intervals(&intervals_set), vec![(1, 12), (14, 15)]);
+        assert_eq!(intervals_set.length(), Some(14));
+    }
+
+    #[test]
+    fn union_and_contains_interval() {
+        let union = set(&[(0, 2), (8, 9)]).union(&set(&[(3, 4), (7, 7)]));
+
+        assert_eq!(intervals(&union), vec![(0, 4), (7, 9)]);
+        assert!(union.contains_interval(&Interval::new(1, 4)));
+        assert!(!union.contains_interval(&Interval::new(4, 7)));
+        assert!(union.contains_interval(&Interval::new(6, 5)));
+    }
+
+    #[test]
+    fn subtract_and_gaps_handle_the_boundaries() {
+        let area = set(&[(0, 20)]);
+
+        assert_eq!(
+            intervals(&area.subtract(&set(&[(0, 3), (8, 10), (18, 25)]))),
+            vec![(4, 7), (11, 17)]
+        );
+        assert!(area.subtract(&set(&[(0, 20)])).is_empty());
+        assert_eq!(intervals(&area.subtract(&set(&[(21, 30)]))), vec![(0, 20)]);
+        assert_eq!(
+            set(&[(0, 3), (5, 5), (9, 20)])
+                .gaps()
+                .map(|gap| (gap.start, gap.end))
+                .collect::<Vec<_>>(),
+            vec![(4, 4), (6, 8)]
+        );
+    }
+
+    #[test]
+    fn intervals_at_the_type_maximum_do_not_overflow() {
+        let top = set(&[
+            (usize::MAX - 1, usize::MAX),
+            (usize::MAX - 5, usize::MAX - 2),
+        ]);
+
+        assert_eq!(intervals(&top), vec![(usize::MAX - 5, usize::MAX)]);
+        assert_eq!(
+            Interval::new(u8::MAX - 1, u8::MAX).relation(&Interval::new(0, 3)),
+            Some(IntervalRelation::After)
+        );
+        assert_eq!(
+            Interval::new(4u8, 7).relation(&Interval::new(8, u8::MAX)),
+            Some(IntervalRelation::Meets)
+        );
+    }
+
+    #[test]
+    fn lengths_that_do_not_fit_the_type_are_none() {
+        assert_eq!(Interval::new(0, usize::MAX).length(), None);
+        assert_eq!(Interval::new(1, usize::MAX).length(), Some(usize::MAX));
+        assert_eq!(Interval::new(isize::MIN, isize::MAX).length(), None);
+        assert_eq!(Interval::new(3, 2).length(), Some(0));
+        assert_eq!(set(&[(0, usize::MAX - 1)]).length(), Some(usize::MAX));
+        assert_eq!(set(&[(0, 5), (6, usize::MAX)]).length(), None);
+    }
+}
@@ -1,5 +1,30 @@
+mod alloc_stats;
+mod animate;
+mod cycle;
+mod interval;
+mod render;
+mod repl;
+mod search;
+mod three_d;
+mod trace;
+mod tui;
 mod two_d_vec;
+mod watch;
+pub use alloc_stats::{measure_allocations, AllocStats};
+pub use animate::{Animation, AnimationFrame};
+pub use cycle::{find_cycle, Cycle, CycleRun};
+pub use interval::{Interval, IntervalRelation, IntervalSet};
+pub use render::{
+    AnsiRenderer, AsciiRenderer, Grid, RenderArgs, RenderCell, RenderOptions, Renderer, Viewport,
+};
+use repl::LoadedProblem;
+pub use repl::{repl_subcommand, run_repl, Session};
+pub use search::{a_star, bfs, dijkstra, SearchResult, SearchStats};
+pub use three_d::{flood_fill_3d, Axis, BoundingBox3, Point3};
+pub use trace::{init_tracing, trace_args};
+pub use tui::{run_part, run_tui, tui_subcommand, InputSource, Registry, Status};
 pub use two_d_vec::{BoundedPoint, PointDirection, RotationDegrees};
+pub use watch::{watch, watch_arg};
 
 use anyhow::Result;
 use ariadne::{Color, Fmt, Label, Report, ReportKind, Source};
@@ -11,17 +36,19 @@ use chumsky::{
     Parser,
 };
 use clap::{
-    builder::PathBufValueParser, Arg, ArgAction, ArgMatches, Command as ClapCommand, ValueHint,
+    builder::PathBufValueParser, Arg, ArgAction, ArgMatches, Args, Command as ClapCommand,
+    FromArgMatches, ValueHint,
 };
 use itertools::Itertools;
 use std::{
     error::Error,
     fmt::{self, Display},
     fs::File,
-    io::Read,
+    io::{BufRead, BufReader, Read},
     ops::Sub,
     path::PathBuf,
 };
+use tracing::info_span;
 
 pub enum CommandResult {
     Isize(isize),
@@ -57,6 +84,34 @@ impl From<String> for CommandResult {
     }
 }
 
+pub trait IntoCommandResult {
+    fn into_command_result(self) -> Result<CommandResult>;
+}
+
+impl IntoCommandResult for isize {
+    fn into_command_result(self) -> Result<CommandResult> {
+        Ok(self.into())
+    }
+}
+
+impl IntoCommandResult for usize {
+    fn into_command_result(self) -> Result<CommandResult> {
+        Ok(self.into())
+    }
+}
+
+impl IntoCommandResult for String {
+    fn into_command_result(self) -> Result<CommandResult> {
+        Ok(self.into())
+    }
+}
+
+impl<T: Into<CommandResult>> IntoCommandResult for Result<T> {
+    fn into_command_result(self) -> Result<CommandResult> {
+        self.map(|result| result.into())
+    }
+}
+
 #[derive(Debug)]
 pub struct ParseError(pub String, pub Vec<Simple<char>>);
 
@@ -84,6 +139,13 @@ pub fn combine_parse_errors(source: &String, errors: &Vec<Simple<char>>) -> Stri
 }
 
 pub fn format_parse_error(source: &String, error: &Simple<char>) -> String {
+    if source.is_empty() {
+        return match error.reason() {
+            SimpleReason::Custom(msg) => msg.clone(),
+            _ => "Unexpected end of input, the file is empty".to_string(),
+        };
+    }
+
     let report = Report::build(ReportKind::Error, (), error.span().start);
 
     let report = match error.reason() {
@@ -160,15 +222,25 @@ pub fn format_parse_error(source: &String, error: &Simple<char>) -> String {
 pub trait Command {
     fn run(&self, args: &ArgMatches) -> Result<CommandResult>;
 
+    fn run_input(&self, file_contents: String, args: &ArgMatches) -> Result<CommandResult>;
+
+    fn input_path(&self, args: &ArgMatches) -> PathBuf;
+
+    fn load(&self, file_contents: String) -> Result<Box<dyn Session + '_>>;
+
     fn get_name(&self) -> &'static str;
 
     fn get_subcommand(&self) -> ClapCommand;
 }
 
+type Stream<T, R> = fn(Box<dyn BufRead>, T) -> R;
+
+type Loader<T, U> = fn(&T) -> Option<Result<U>>;
+
 pub struct Problem<T, U, R>
 where
     T: Clone,
-    R: Into<CommandResult>,
+    R: IntoCommandResult,
 {
     name: &'static str,
     subcommand: ClapCommand,
@@ -177,34 +249,42 @@ where
     parse_args: fn(&ArgMatches) -> T,
     parse_file: fn(String) -> Result<U>,
     run: fn(U, T) -> R,
+    stream: Option<Stream<T, R>>,
+    source: Option<Loader<T, U>>,
 }
 
 impl<T, U, R> Problem<T, U, R>
 where
-    T: Clone,
-    R: Into<CommandResult>,
+    T: Clone + Args + FromArgMatches,
+    R: IntoCommandResult,
 {
     pub fn new(
         name: &'static str,
         help: &str,
         file_help: &str,
-        args: Vec<Arg>,
-        parse_args: fn(&ArgMatches) -> T,
         parse_file: fn(String) -> Result<U>,
         run: fn(U, T) -> R,
     ) -> Self {
-        let subcommand = subcommand(name, help, file_help).args(args);
+        let subcommand = T::augment_args(subcommand(name, help, file_help));
         Problem {
             name,
             subcommand,
             part1_data: None,
             part2_data: None,
-            parse_args,
+            parse_args: parse_arguments::<T>,
             parse_file,
             run,
+            stream: None,
+            source: None,
         }
     }
+}
 
+impl<T, U, R> Problem<T, U, R>
+where
+    T: Clone,
+    R: IntoCommandResult,
+{
     pub fn with_part1(mut self, argument: T, docs: &str) -> Self {
         self.subcommand = self.subcommand.with_part1(docs);
         self.part1_data = Some(argument);
@@ -217,46 +297,94 @@ where
         self
     }
 
-    fn parse_matches(&self, args: &ArgMatches) -> (String, T) {
-        let (file_path, arg) = match args.subcommand_name() {
-            Some(name) => {
-                let mut file = PathBuf::new();
-                file.push(format!("{}/input.txt", self.name));
-                let arg = self
-                    .part1_data
-                    .iter()
-                    .map(|arg| ("part1", arg))
-                    .chain(self.part2_data.iter().map(|arg| ("part2", arg)))
-                    .filter_map(|(part_name, arg)| match name {
-                        a if a == part_name => Some(arg),
-                        _ => None,
-                    })
-                    .next()
-                    .expect("At least one part");
-
-                (file, arg.clone())
-            }
-            _ => (
-                args.get_one::<PathBuf>("file")
-                    .expect("File is required")
-                    .clone(),
-                (self.parse_args)(args),
-            ),
-        };
-        let file_contents = file_to_string(&file_path).expect("Can read file");
-        (file_contents, arg)
+    pub fn with_stream(mut self, stream: Stream<T, R>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    pub fn with_source(mut self, source: Loader<T, U>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    fn part_argument(&self, part: &str) -> T {
+        self.part1_data
+            .iter()
+            .map(|arg| ("part1", arg))
+            .chain(self.part2_data.iter().map(|arg| ("part2", arg)))
+            .filter_map(|(part_name, arg)| match part {
+                a if a == part_name => Some(arg),
+                _ => None,
+            })
+            .next()
+            .expect("At least one part")
+            .clone()
+    }
+
+    fn argument(&self, args: &ArgMatches) -> T {
+        match args.subcommand_name() {
+            Some(part) => self.part_argument(part),
+            _ => (self.parse_args)(args),
+        }
     }
 }
 
 impl<T, U, R> Command for Problem<T, U, R>
 where
-    T: Clone,
-    R: Into<CommandResult>,
+    T: Clone + fmt::Debug,
+    U: Clone + fmt::Debug,
+    R: IntoCommandResult,
 {
     fn run(&self, args: &ArgMatches) -> Result<CommandResult> {
-        let (file_contents, arg) = self.parse_matches(args);
-        let parse_result = (self.parse_file)(file_contents);
-        parse_result.map(|parsed| (self.run)(parsed, arg).into())
+        if let Some(source) = self.source {
+            let arg = self.argument(args);
+            if let Some(parsed) =
+                info_span!("parse_file", day = self.name).in_scope(|| source(&arg))
+            {
+                return info_span!("run", day = self.name)
+                    .in_scope(|| (self.run)(parsed?, arg).into_command_result());
+            }
+        }
+
+        match self.stream {
+            Some(stream) => {
+                let reader = BufReader::new(File::open(self.input_path(args))?);
+                let arg = self.argument(args);
+                info_span!("run", day = self.name)
+                    .in_scope(|| stream(Box::new(reader), arg).into_command_result())
+            }
+            None => {
+                let file_contents = file_to_string(&self.input_path(args))?;
+                self.run_input(file_contents, args)
+            }
+        }
+    }
+
+    fn run_input(&self, file_contents: String, args: &ArgMatches) -> Result<CommandResult> {
+        let arg = self.argument(args);
+        let parsed = info_span!("parse_file", day = self.name)
+            .in_scope(|| (self.parse_file)(file_contents))?;
+        info_span!("run", day = self.name)
+            .in_scope(|| (self.run)(parsed, arg).into_command_result())
+    }
+
+    fn input_path(&self, args: &ArgMatches) -> PathBuf {
+        match args.subcommand_name() {
+            Some(_) => PathBuf::from(format!("{}/input.txt", self.name)),
+            _ => args
+                .get_one::<PathBuf>("file")
+                .expect("File is required")
+                .clone(),
+        }
+    }
+
+    fn load(&self, file_contents: String) -> Result<Box<dyn Session + '_>> {
+        (self.parse_file)(file_contents).map(|input| {
+            Box::new(LoadedProblem {
+                problem: self,
+                input,
+            }) as Box<dyn Session>
+        })
     }
 
     fn get_name(&self) -> &'static str {
@@ -269,29 +397,20 @@ where
 }
 
 fn file_arg(help: &str) -> Arg {
-    single_arg("file", 'f', help)
-        .value_hint(ValueHint::FilePath)
-        .value_parser(PathBufValueParser::new())
-}
-
-pub fn single_arg(name: &'static str, short: char, help: &str) -> Arg {
-    Arg::new(name)
-        .short(short)
-        .long(name)
+    Arg::new("file")
+        .short('f')
+        .long("file")
         .num_args(1)
         .help(help.to_string())
         .required(true)
         .action(ArgAction::Set)
-        .value_name(name.to_ascii_uppercase())
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .value_parser(PathBufValueParser::new())
 }
 
-pub fn flag_arg(name: &'static str, short: char, help: &str) -> Arg {
-    Arg::new(name)
-        .short(short)
-        .long(name)
-        .help(help.to_string())
-        .num_args(0)
-        .action(ArgAction::SetTrue)
+fn parse_arguments<T: FromArgMatches>(args: &ArgMatches) -> T {
+    T::from_arg_matches(args).expect("Valid arguments")
 }
 
 fn subcommand(name: &'static str, help: &str, file_help: &str) -> ClapCommand {
@@ -299,6 +418,7 @@ fn subcommand(name: &'static str, help: &str, file_help: &str) -> ClapCommand {
         .about(help.to_string())
         .arg_required_else_help(true)
         .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
         .arg(file_arg(file_help))
 }
 
@@ -363,6 +483,18 @@ pub fn parse_lines<T>(
     line_parser.separated_by(text::newline()).allow_trailing()
 }
 
+pub fn parse_grid<T>(
+    cell_parser: impl Parser<char, T, Error = Simple<char>>,
+) -> impl Parser<char, Vec<Vec<T>>, Error = Simple<char>> {
+    parse_lines(cell_parser.repeated().at_least(1)).try_map(|rows, span| {
+        if rows.iter().map(|row| row.len()).all_equal() {
+            Ok(rows)
+        } else {
+            Err(Simple::custom(span, "Every row must be the same width"))
+        }
+    })
+}
+
 pub fn parse_chunks<T>(
     chunker: impl Parser<char, Vec<Vec<char>>, Error = Simple<char>>,
     chunk_parser: impl Parser<char, T, Error = Simple<char>>,
@@ -413,3 +545,110 @@ where
         y - x
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_usize_round_trips() {
+        [0usize, 7, 42, 1_000_000, usize::MAX]
+            .into_iter()
+            .for_each(|number| {
+                assert_eq!(parse_usize().parse(number.to_string()), Ok(number));
+            });
+    }
+
+    #[test]
+    fn parse_usize_with_radix_round_trips() {
+        [0usize, 10, 255, 4096].into_iter().for_each(|number| {
+            assert_eq!(
+                parse_usize_with_radix(16).parse(format!("{:x}", number)),
+                Ok(number)
+            );
+            assert_eq!(
+                parse_usize_with_radix(2).parse(format!("{:b}", number)),
+                Ok(number)
+            );
+        });
+    }
+
+    #[test]
+    fn parse_isize_round_trips() {
+        [0isize, 5, -5, 123_456, -123_456, isize::MIN, isize::MAX]
+            .into_iter()
+            .for_each(|number| {
+                assert_eq!(parse_isize().parse(number.to_string()), Ok(number));
+            });
+    }
+
+    #[test]
+    fn parse_isize_rejects_overflow() {
+        assert!(parse_isize().parse("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn parse_lines_round_trips() {
+        let numbers = vec![1usize, 22, 333];
+        let text = numbers.iter().join("\n");
+
+        assert_eq!(
+            parse_lines(parse_usize())
+                .then_ignore(end())
+                .parse(text.clone()),
+            Ok(numbers.clone())
+        );
+        assert_eq!(
+            parse_lines(parse_usize())
+                .then_ignore(end())
+                .parse(text + "\n"),
+            Ok(numbers)
+        );
+    }
+
+    #[test]
+    fn parse_between_blank_lines_round_trips() {
+        let groups = vec![vec![1usize, 2], vec![3], vec![4, 5, 6]];
+        let text = groups
+            .iter()
+            .map(|group| group.iter().join("\n"))
+            .join("\n\n");
+
+        assert_eq!(
+            parse_between_blank_lines(parse_lines(parse_usize()).then_ignore(end())).parse(text),
+            Ok(groups)
+        );
+    }
+
+    #[test]
+    fn parse_between_blank_lines_reports_chunk_errors() {
+        assert!(
+            parse_between_blank_lines(parse_lines(parse_usize()).then_ignore(end()))
+                .parse("1\n2\n\nx")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_errors_format_for_empty_input() {
+        let source = String::new();
+        let errors = parse_usize()
+            .then_ignore(end())
+            .parse(source.clone())
+            .expect_err("Empty input is not a number");
+
+        assert_eq!(
+            combine_parse_errors(&source, &errors),
+            "Unexpected end of input, the file is empty"
+        );
+    }
+
+    #[test]
+    fn parse_grid_rejects_ragged_rows() {
+        assert_eq!(
+            parse_grid(just('#')).parse("##\n##"),
+            Ok(vec![vec!['#', '#'], vec!['#', '#']])
+        );
+        assert!(parse_grid(just('#')).parse("##\n#").is_err());
+    }
+}
@@ -1,10 +1,15 @@
 #![feature(lazy_cell)]
 
-use adventofcode2022::{Command, CommandResult};
+use adventofcode2022::{
+    init_tracing, measure_allocations, repl_subcommand, run_repl, run_tui, trace_args,
+    tui_subcommand, watch, watch_arg, AllocStats, Command, CommandResult,
+};
 use anyhow::Result;
 use clap::Command as ClapCommand;
 use std::{
     cell::LazyCell,
+    io::{stdin, stdout},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
@@ -33,11 +38,17 @@ mod day22;
 mod day23;
 mod day24;
 mod day25;
+#[cfg(test)]
+mod fuzz;
+#[cfg(test)]
+mod random;
+#[cfg(test)]
+mod samples;
 
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-fn main() -> Result<()> {
-    let commands: Vec<(&str, LazyCell<Box<dyn Command>>)> = vec![
+fn commands() -> Vec<(&'static str, LazyCell<Box<dyn Command>>)> {
+    vec![
         day01::DAY_01,
         day02::DAY_02,
         day03::DAY_03,
@@ -66,10 +77,18 @@ fn main() -> Result<()> {
     ]
     .into_iter()
     .map(|command| (command.get_name(), command))
-    .collect();
+    .collect()
+}
+
+fn main() -> Result<()> {
+    let commands = commands();
     let subcommands = commands
         .iter()
         .map(|(_, command)| command.get_subcommand())
+        .chain([
+            repl_subcommand(commands.iter().map(|(name, _)| *name).collect()),
+            tui_subcommand(),
+        ])
         .collect::<Vec<_>>();
 
     let matches = ClapCommand::new("Advent of Code 2022")
@@ -77,8 +96,39 @@ fn main() -> Result<()> {
         .about("Run the advent of code problems from this main program")
         .arg_required_else_help(true)
         .subcommand_required(true)
+        .arg(watch_arg())
+        .args(trace_args())
         .subcommands(subcommands)
         .get_matches();
+    init_tracing(&matches)?;
+
+    if matches.subcommand_matches("tui").is_some() {
+        return run_tui(crate::commands);
+    }
+
+    if let Some(args) = matches.subcommand_matches("repl") {
+        let day = args.get_one::<String>("day").expect("Day is required");
+        let (_, command) = commands
+            .iter()
+            .find(|(name, _)| name == day)
+            .expect("Day is registered");
+        let file = args
+            .get_one::<PathBuf>("file")
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(format!("{}/input.txt", day)));
+        return run_repl(&***command, file, stdin().lock(), stdout());
+    }
+
+    if matches.get_flag("watch") {
+        let (_, command) = commands
+            .iter()
+            .find(|(name, _)| matches.subcommand_name() == Some(*name))
+            .expect("A day is required");
+        let args = matches
+            .subcommand_matches(command.get_name())
+            .expect("Day is matched");
+        return watch(&***command, args, stdout());
+    }
 
     commands
         .into_iter()
@@ -86,16 +136,19 @@ fn main() -> Result<()> {
             matches.subcommand_matches(name).map(|args| {
                 println!("=============Running {:}=============", command.get_name());
                 let now = Instant::now();
-                let result = command.run(args);
+                let (result, allocations) = measure_allocations(|| command.run(args));
                 let elapsed = now.elapsed();
-                result.map(|r| (r, elapsed))
+                result.map(|r| (r, elapsed, allocations))
             })
         })
-        .collect::<Result<Vec<(CommandResult, Duration)>>>()
+        .collect::<Result<Vec<(CommandResult, Duration, Option<AllocStats>)>>>()
         .map(|results| {
-            results.into_iter().for_each(|(result, elapsed)| {
-                println!("{}", result);
-                println!("Took {:#?} to run", elapsed)
-            })
+            results
+                .into_iter()
+                .for_each(|(result, elapsed, allocations)| {
+                    println!("{}", result);
+                    println!("Took {:#?} to run", elapsed);
+                    allocations.iter().for_each(|stats| println!("{}", stats));
+                })
         })
 }
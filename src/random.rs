@@ -0,0 +1,54 @@
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    pub fn usize_in(&mut self, range: RangeInclusive<usize>) -> usize {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as usize
+    }
+
+    pub fn isize_in(&mut self, range: RangeInclusive<isize>) -> isize {
+        let span = range.end().abs_diff(*range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as isize
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        items
+            .get(self.usize_in(0..=items.len() - 1))
+            .expect("Items are not empty")
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        (1..items.len()).rev().for_each(|index| {
+            let other = self.usize_in(0..=index);
+            items.swap(index, other);
+        });
+    }
+}
+
+pub fn property(cases: usize, max_size: usize, mut check: impl FnMut(&mut Rng, usize)) {
+    (0..cases).for_each(|case| {
+        let size = 1 + case * max_size / cases.max(1);
+        check(&mut Rng::new(case as u64), size);
+    });
+}
@@ -0,0 +1,289 @@
+use crate::Animation;
+use anyhow::Result;
+use ariadne::{Color, Fmt};
+use clap::{value_parser, ArgGroup, Args, ValueHint};
+use itertools::Itertools;
+use std::{
+    cmp::{max, min},
+    path::PathBuf,
+};
+
+pub trait RenderCell {
+    fn to_char(&self) -> char;
+
+    fn color(&self) -> Option<Color> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub min_x: isize,
+    pub min_y: isize,
+    pub max_x: isize,
+    pub max_y: isize,
+}
+
+impl Viewport {
+    pub fn from_points(points: impl IntoIterator<Item = (isize, isize)>) -> Option<Viewport> {
+        points.into_iter().fold(None, |acc, (x, y)| match acc {
+            None => Some(Viewport {
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            }),
+            Some(viewport) => Some(Viewport {
+                min_x: min(viewport.min_x, x),
+                min_y: min(viewport.min_y, y),
+                max_x: max(viewport.max_x, x),
+                max_y: max(viewport.max_y, y),
+            }),
+        })
+    }
+
+    fn parse(value: &str) -> Result<Viewport, String> {
+        match value
+            .split(',')
+            .map(|number| number.trim().parse::<isize>())
+            .collect::<Result<Vec<_>, _>>()
+            .as_deref()
+        {
+            Ok([min_x, min_y, max_x, max_y]) if min_x <= max_x && min_y <= max_y => Ok(Viewport {
+                min_x: *min_x,
+                min_y: *min_y,
+                max_x: *max_x,
+                max_y: *max_y,
+            }),
+            _ => Err("Expected x0,y0,x1,y1 with x0 <= x1 and y0 <= y1".to_string()),
+        }
+    }
+
+    pub fn crop(&self, other: &Viewport) -> Viewport {
+        Viewport {
+            min_x: max(self.min_x, other.min_x),
+            min_y: max(self.min_y, other.min_y),
+            max_x: min(self.max_x, other.max_x),
+            max_y: min(self.max_y, other.max_y),
+        }
+    }
+}
+
+pub trait Grid {
+    type Cell: RenderCell;
+
+    fn viewport(&self) -> Viewport;
+
+    fn cell(&self, x: isize, y: isize) -> Self::Cell;
+}
+
+pub trait Renderer {
+    fn render_cell(&self, cell: &impl RenderCell) -> String;
+
+    fn render(&self, grid: &impl Grid, viewport: Option<&Viewport>) -> String {
+        let full = grid.viewport();
+        let view = viewport.map(|viewport| full.crop(viewport)).unwrap_or(full);
+
+        (view.min_y..=view.max_y)
+            .map(|y| {
+                (view.min_x..=view.max_x)
+                    .map(|x| self.render_cell(&grid.cell(x, y)))
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+}
+
+pub struct AsciiRenderer;
+
+impl Renderer for AsciiRenderer {
+    fn render_cell(&self, cell: &impl RenderCell) -> String {
+        cell.to_char().to_string()
+    }
+}
+
+pub struct AnsiRenderer;
+
+impl Renderer for AnsiRenderer {
+    fn render_cell(&self, cell: &impl RenderCell) -> String {
+        match cell.color() {
+            Some(color) => cell.to_char().fg(color).to_string(),
+            None => cell.to_char().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub every: Option<usize>,
+    pub color: bool,
+    pub viewport: Option<Viewport>,
+    pub animation: Option<Animation>,
+}
+
+#[derive(Debug, Clone, Default, Args)]
+#[command(group(ArgGroup::new("rendering").args(["render", "animate"]).multiple(true)))]
+pub struct RenderArgs {
+    #[arg(long, help = "Renders the final state of the simulation")]
+    render: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "rendering",
+        help = "Also renders every Nth step of the simulation"
+    )]
+    render_every: Option<usize>,
+    #[arg(long, requires = "rendering", help = "Renders with ANSI colours")]
+    color: bool,
+    #[arg(
+        long,
+        value_name = "x0,y0,x1,y1",
+        value_parser = Viewport::parse,
+        requires = "rendering",
+        allow_hyphen_values = true,
+        help = "Only renders the cells between the two corners"
+    )]
+    viewport: Option<Viewport>,
+    #[arg(
+        long,
+        help = "Records the simulation and plays it back once the run finishes, sampling long runs"
+    )]
+    animate: bool,
+    #[arg(
+        long,
+        value_name = "FPS",
+        default_value_t = 10,
+        value_parser = value_parser!(u32).range(1..),
+        requires = "animate",
+        help = "Frames per second of the animation playback"
+    )]
+    fps: u32,
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        requires = "animate",
+        help = "Writes each animation frame to a directory as it is recorded instead of playing them"
+    )]
+    export: Option<PathBuf>,
+}
+
+impl RenderArgs {
+    pub fn options(&self) -> Option<RenderOptions> {
+        (self.render || self.animate).then(|| RenderOptions {
+            every: self.render_every,
+            color: self.color,
+            viewport: self.viewport,
+            animation: self
+                .animate
+                .then(|| Animation::new(self.fps, self.export.clone())),
+        })
+    }
+}
+
+impl RenderOptions {
+    pub fn is_render_step(&self, step: usize) -> bool {
+        self.every
+            .or(self.animation.as_ref().map(|_| 1))
+            .filter(|every| *every > 0)
+            .map(|every| step.is_multiple_of(every))
+            .unwrap_or(false)
+    }
+
+    pub fn render(&self, grid: &impl Grid) -> String {
+        if self.color {
+            AnsiRenderer.render(grid, self.viewport.as_ref())
+        } else {
+            AsciiRenderer.render(grid, self.viewport.as_ref())
+        }
+    }
+
+    pub fn print(&self, title: &str, grid: &impl Grid) {
+        match &self.animation {
+            Some(animation) => animation.record(title, self.render(grid)),
+            None => {
+                println!("{}", title);
+                println!("{}", self.render(grid));
+                println!();
+            }
+        }
+    }
+
+    pub fn finish(&self) -> Result<()> {
+        match &self.animation {
+            Some(animation) => animation.finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Command, FromArgMatches};
+
+    struct Cell(char);
+
+    impl RenderCell for Cell {
+        fn to_char(&self) -> char {
+            self.0
+        }
+
+        fn color(&self) -> Option<Color> {
+            (self.0 == '#').then_some(Color::Red)
+        }
+    }
+
+    struct Diagonal;
+
+    impl Grid for Diagonal {
+        type Cell = Cell;
+
+        fn viewport(&self) -> Viewport {
+            Viewport::from_points([(-1, -1), (2, 2)]).unwrap()
+        }
+
+        fn cell(&self, x: isize, y: isize) -> Cell {
+            Cell(if x == y { '#' } else { '.' })
+        }
+    }
+
+    fn options(args: &[&str]) -> Option<RenderOptions> {
+        let command = RenderArgs::augment_args(Command::new("render"));
+        let matches = command
+            .try_get_matches_from(["render"].iter().chain(args))
+            .unwrap();
+        RenderArgs::from_arg_matches(&matches).unwrap().options()
+    }
+
+    #[test]
+    fn ascii_renderer_draws_and_crops_the_grid() {
+        assert_eq!(
+            AsciiRenderer.render(&Diagonal, None),
+            "#...\n.#..\n..#.\n...#"
+        );
+        let viewport = Viewport::parse("0,-5,1,1").unwrap();
+        assert_eq!(
+            AsciiRenderer.render(&Diagonal, Some(&viewport)),
+            "..\n#.\n.#"
+        );
+    }
+
+    #[test]
+    fn ansi_renderer_colors_cells() {
+        let rendered = AnsiRenderer.render(&Diagonal, Some(&Viewport::parse("0,0,1,0").unwrap()));
+
+        assert_eq!(rendered, format!("{}.", '#'.fg(Color::Red)));
+        assert_ne!(rendered, "#.");
+    }
+
+    #[test]
+    fn viewport_argument_crops_renders() {
+        let cropped = options(&["--render", "--viewport", "-1,-1,0,0"]).unwrap();
+
+        assert_eq!(cropped.render(&Diagonal), "#.\n.#");
+        assert!(Viewport::parse("1,2,3").is_err());
+        assert!(Viewport::parse("3,0,1,2").is_err());
+        assert!(options(&[]).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{
+    file_to_string, measure_allocations, Command, CommandResult, IntoCommandResult, Problem,
+};
+use anyhow::{anyhow, Result};
+use clap::{builder::PathBufValueParser, Arg, ArgAction, ArgMatches, Command as ClapCommand};
+use itertools::Itertools;
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    io::{BufRead, Write},
+    iter::once,
+    path::PathBuf,
+    time::Instant,
+};
+
+pub trait Session {
+    fn run(&self, args: &ArgMatches) -> Result<CommandResult>;
+
+    fn input(&self) -> String;
+
+    fn arguments(&self, args: &ArgMatches) -> String;
+}
+
+pub(crate) struct LoadedProblem<'a, T, U, R>
+where
+    T: Clone,
+    R: IntoCommandResult,
+{
+    pub(crate) problem: &'a Problem<T, U, R>,
+    pub(crate) input: U,
+}
+
+impl<T, U, R> Session for LoadedProblem<'_, T, U, R>
+where
+    T: Clone + Debug,
+    U: Clone + Debug,
+    R: IntoCommandResult,
+{
+    fn run(&self, args: &ArgMatches) -> Result<CommandResult> {
+        (self.problem.run)(self.input.clone(), (self.problem.parse_args)(args))
+            .into_command_result()
+    }
+
+    fn input(&self) -> String {
+        format!("{:#?}", self.input)
+    }
+
+    fn arguments(&self, args: &ArgMatches) -> String {
+        format!("{:#?}", (self.problem.parse_args)(args))
+    }
+}
+
+pub fn repl_subcommand(days: Vec<&'static str>) -> ClapCommand {
+    ClapCommand::new("repl")
+        .about("Loads a day's input once, then re-runs it with different arguments")
+        .arg(
+            Arg::new("day")
+                .help("The day to explore")
+                .required(true)
+                .value_parser(days),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .help("Path to the input file. Defaults to the day's input.txt")
+                .num_args(1)
+                .action(ArgAction::Set)
+                .value_parser(PathBufValueParser::new()),
+        )
+}
+
+const HELP: &str = "Commands:
+  set <arg> [value]  Sets an argument, flags take no value
+  unset <arg>        Removes an argument
+  args               Lists the arguments that are set
+  run                Runs the day with the current arguments
+  input              Prints the parsed input
+  arguments          Prints the parsed arguments
+  reload             Reads and parses the input file again
+  help               Prints this message
+  quit               Leaves the repl";
+
+struct Repl<'a> {
+    command: &'a dyn Command,
+    file: PathBuf,
+    session: Box<dyn Session + 'a>,
+    settings: BTreeMap<String, Vec<String>>,
+}
+
+enum Step {
+    Continue(String),
+    Quit,
+}
+
+pub fn run_repl(
+    command: &dyn Command,
+    file: PathBuf,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    let session = load(command, &file, &mut output)?;
+    let mut repl = Repl {
+        command,
+        file,
+        session,
+        settings: BTreeMap::new(),
+    };
+
+    write!(output, "{}> ", command.get_name())?;
+    output.flush()?;
+    for line in input.lines() {
+        match repl.step(&line?, &mut output) {
+            Ok(Step::Continue(message)) if message.is_empty() => (),
+            Ok(Step::Continue(message)) => writeln!(output, "{}", message)?,
+            Ok(Step::Quit) => return Ok(()),
+            Err(error) => writeln!(output, "Error: {}", error)?,
+        }
+        write!(output, "{}> ", command.get_name())?;
+        output.flush()?;
+    }
+    writeln!(output)?;
+
+    Ok(())
+}
+
+fn load<'a>(
+    command: &'a dyn Command,
+    file: &PathBuf,
+    output: &mut impl Write,
+) -> Result<Box<dyn Session + 'a>> {
+    let now = Instant::now();
+    let session = command.load(file_to_string(file)?)?;
+    writeln!(output, "Parsed {} in {:#?}", file.display(), now.elapsed())?;
+    Ok(session)
+}
+
+impl<'a> Repl<'a> {
+    fn step(&mut self, line: &str, output: &mut impl Write) -> Result<Step> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let message = match words.as_slice() {
+            [] => String::new(),
+            ["quit"] | ["exit"] => return Ok(Step::Quit),
+            ["help"] => HELP.to_string(),
+            ["set", name, values @ ..] => {
+                let arg = self.find_arg(name)?;
+                let takes_values = arg.get_action().takes_values();
+                match (takes_values, values) {
+                    (true, []) => return Err(anyhow!("{} needs a value", name)),
+                    (false, [_, ..]) => {
+                        return Err(anyhow!("{} is a flag and takes no value", name))
+                    }
+                    _ => (),
+                }
+                let id = arg.get_id().to_string();
+                self.settings
+                    .insert(id, values.iter().map(|value| value.to_string()).collect());
+                self.describe_settings()
+            }
+            ["unset", name] => {
+                let id = self.find_arg(name)?.get_id().to_string();
+                self.settings.remove(&id);
+                self.describe_settings()
+            }
+            ["args"] => self.describe_settings(),
+            ["run"] => {
+                let matches = self.matches()?;
+                let now = Instant::now();
+                let (result, allocations) = measure_allocations(|| self.session.run(&matches));
+                let elapsed = now.elapsed();
+                once(format!("{}\nTook {:#?} to run", result?, elapsed))
+                    .chain(allocations.map(|stats| stats.to_string()))
+                    .join("\n")
+            }
+            ["input"] => self.session.input(),
+            ["arguments"] => self.session.arguments(&self.matches()?),
+            ["reload"] => {
+                self.session = load(self.command, &self.file, output)?;
+                String::new()
+            }
+            [other, ..] => return Err(anyhow!("Unknown command {}, try help", other)),
+        };
+
+        Ok(Step::Continue(message))
+    }
+
+    fn find_arg(&self, name: &str) -> Result<Arg> {
+        self.command
+            .get_subcommand()
+            .get_arguments()
+            .find(|arg| arg.get_id() == name || arg.get_long() == Some(name))
+            .filter(|arg| arg.get_id() != "file")
+            .cloned()
+            .ok_or_else(|| anyhow!("{} has no argument {}", self.command.get_name(), name))
+    }
+
+    fn describe_settings(&self) -> String {
+        self.settings
+            .iter()
+            .map(|(id, values)| {
+                format!("{} {}", id, values.join(" "))
+                    .trim_end()
+                    .to_string()
+            })
+            .join("\n")
+    }
+
+    fn matches(&self) -> Result<ArgMatches> {
+        let subcommand = self.command.get_subcommand();
+        let settings = self
+            .settings
+            .iter()
+            .flat_map(|(id, values)| {
+                let flag = subcommand
+                    .get_arguments()
+                    .find(|arg| arg.get_id() == id.as_str())
+                    .and_then(|arg| arg.get_long())
+                    .map(|long| format!("--{}", long))
+                    .unwrap_or_else(|| format!("--{}", id));
+                [flag].into_iter().chain(values.iter().cloned())
+            })
+            .collect::<Vec<_>>();
+        let file = self.file.display().to_string();
+
+        subcommand
+            .try_get_matches_from(
+                [self.command.get_name().to_string(), "-f".to_string(), file]
+                    .into_iter()
+                    .chain(settings),
+            )
+            .map_err(|error| anyhow!("{}", error.render()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_lines, parse_usize, ParseError};
+    use chumsky::{primitive::end, Parser};
+    use clap::Args;
+    use std::{env, fs, process};
+
+    #[derive(Debug, Clone, Args)]
+    struct SumArguments {
+        #[arg(short, long, help = "How many numbers to sum")]
+        number: usize,
+    }
+
+    #[test]
+    fn repl_reruns_a_loaded_input_with_new_arguments() {
+        let problem = Problem::new(
+            "sum",
+            "Sums the largest numbers",
+            "Numbers, one per line",
+            |file| {
+                parse_lines(parse_usize())
+                    .then_ignore(end())
+                    .parse(file.clone())
+                    .map_err(|errors| ParseError(file, errors).into())
+            },
+            |mut numbers: Vec<usize>, arguments: SumArguments| {
+                numbers.sort_unstable_by(|a, b| b.cmp(a));
+                numbers.into_iter().take(arguments.number).sum::<usize>()
+            },
+        );
+        let mut file = env::temp_dir();
+        file.push(format!("repl-{}.txt", process::id()));
+        fs::write(&file, "3\n10\n7\n").unwrap();
+
+        let mut output = Vec::new();
+        run_repl(
+            &problem,
+            file.clone(),
+            "set number 1\nrun\nset number 2\nrun\nset bogus 1\ninput\nquit\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        fs::remove_file(&file).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("sum> 10\nTook"));
+        assert!(output.contains("sum> 17\nTook"));
+        assert!(output.contains("Error: sum has no argument bogus"));
+        assert!(output.contains("[\n    3,\n    10,\n    7,\n]"));
+    }
+}
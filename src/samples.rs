@@ -0,0 +1,121 @@
+use crate::commands;
+use itertools::Itertools;
+
+const SAMPLES: &[(&str, &[&str], &str)] = &[
+    ("day01", &["-n", "1"], "24000"),
+    ("day01", &["-n", "3"], "45000"),
+    ("day02", &["-s", "roshambo"], "15"),
+    ("day02", &["-s", "outcome"], "12"),
+    ("day03", &["-s", "-g", "1"], "157"),
+    ("day03", &["-g", "3"], "70"),
+    ("day04", &["-o", "full"], "2"),
+    ("day04", &["-o", "any"], "4"),
+    ("day05", &[], "CMZ"),
+    ("day05", &["-u"], "MCD"),
+    ("day06", &["-n", "4"], "7"),
+    ("day06", &["-n", "14"], "19"),
+    ("day07", &["-t", "100000"], "95437"),
+    ("day07", &["-s", "30000000"], "24933642"),
+    ("day08", &["-s", "visible-trees"], "21"),
+    ("day08", &["-s", "best-tree"], "8"),
+    ("day09", &["-l", "2"], "13"),
+    ("day09", &["-l", "10"], "1"),
+    ("day10", &["-c", "20,60,100,140,180,220"], "13140"),
+    ("day11", &["-r", "20", "-c", "3"], "10605"),
+    ("day11", &["-r", "10000", "-a"], "2713310158"),
+    ("day12", &[], "31"),
+    ("day12", &["-e"], "29"),
+    ("day13", &["-o", "local"], "13"),
+    ("day13", &["-o", "global"], "140"),
+    ("day14", &[], "24"),
+    ("day14", &["-b"], "93"),
+    ("day15", &["-l", "10"], "26"),
+    ("day15", &["-a", "20"], "56000011"),
+    ("day16", &["-t", "30", "-e", "1"], "1651"),
+    ("day16", &["-t", "26", "-e", "2"], "1707"),
+    ("day17", &["-n", "2022"], "3068"),
+    ("day17", &["-n", "1000000000000"], "1514285714288"),
+    ("day18", &[], "64"),
+    ("day18", &["-a"], "58"),
+    ("day20", &["-i", "1", "-k", "1"], "3"),
+    ("day20", &["-i", "10", "-k", "811589153"], "1623178306"),
+    ("day21", &[], "152"),
+    ("day21", &["-e"], "301"),
+    ("day22", &[], "6032"),
+    ("day22", &["-r", "4"], "5031"),
+    ("day23", &["-r", "10"], "110"),
+    ("day23", &["-e"], "20"),
+    ("day24", &["-t", "1"], "18"),
+    ("day24", &["-t", "3"], "54"),
+    ("day25", &[], "2=-1=0"),
+];
+
+const SLOW_SAMPLES: &[(&str, &[&str], &str)] = &[
+    ("day19", &["-t", "24", "-s", "quality-level-sum"], "33"),
+    (
+        "day19",
+        &["-t", "24", "-l", "2", "-s", "product-geodes"],
+        "108",
+    ),
+];
+
+fn run_sample(day: &str, args: &[&str]) -> String {
+    let (_, command) = commands()
+        .into_iter()
+        .find(|(name, _)| *name == day)
+        .expect("Day is registered");
+    let file = format!("{}/sample.txt", day);
+    let matches = command
+        .get_subcommand()
+        .try_get_matches_from([day, "-f", &file].into_iter().chain(args.iter().copied()))
+        .expect("Valid arguments");
+
+    command
+        .run(&matches)
+        .map(|result| result.to_string())
+        .unwrap_or_else(|error| format!("Error: {}", error))
+}
+
+fn check_samples(samples: &[(&str, &[&str], &str)]) {
+    let failures = samples
+        .iter()
+        .filter_map(|(day, args, expected)| {
+            let actual = run_sample(day, args);
+            if actual == *expected {
+                None
+            } else {
+                Some(format!(
+                    "{} {:?}: expected {}, got {}",
+                    day, args, expected, actual
+                ))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn samples_match_expected_answers() {
+    check_samples(SAMPLES);
+}
+
+#[test]
+#[ignore = "takes minutes without optimisations, run with --ignored"]
+fn slow_samples_match_expected_answers() {
+    check_samples(SLOW_SAMPLES);
+}
+
+#[test]
+fn every_day_has_samples() {
+    let covered = SAMPLES
+        .iter()
+        .chain(SLOW_SAMPLES.iter())
+        .map(|(day, _, _)| *day)
+        .unique()
+        .collect::<Vec<_>>();
+
+    commands().into_iter().for_each(|(name, _)| {
+        assert!(covered.contains(&name), "{} has no sample answers", name);
+    });
+}
@@ -0,0 +1,251 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+use tracing::trace;
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<S, C> {
+    pub path: Vec<S>,
+    pub cost: C,
+    pub stats: SearchStats,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    pub visited: usize,
+    pub expanded: usize,
+    pub max_frontier: usize,
+}
+
+struct SearchNode<S> {
+    state: S,
+    parent: Option<usize>,
+}
+
+fn build_path<S: Clone>(nodes: &[SearchNode<S>], mut index: usize) -> Vec<S> {
+    let mut path = Vec::new();
+    loop {
+        let node = nodes.get(index).expect("Valid index");
+        path.push(node.state.clone());
+        match node.parent {
+            Some(parent) => index = parent,
+            None => break,
+        }
+    }
+    path.reverse();
+    path
+}
+
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchResult<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut nodes = Vec::new();
+    let mut depths = HashMap::new();
+    let mut queue = VecDeque::new();
+    let mut stats = SearchStats::default();
+
+    starts.into_iter().for_each(|start| {
+        if let Entry::Vacant(entry) = depths.entry(start.clone()) {
+            entry.insert(0usize);
+            queue.push_back((nodes.len(), 0usize));
+            nodes.push(SearchNode {
+                state: start,
+                parent: None,
+            });
+        }
+    });
+
+    let mut layer = None;
+    while let Some((index, depth)) = queue.pop_front() {
+        if layer != Some(depth) {
+            layer = Some(depth);
+            trace!(depth, frontier = queue.len() + 1, "Expanding bfs layer");
+        }
+        stats.expanded += 1;
+        let current = nodes.get(index).expect("Valid index").state.clone();
+
+        if is_goal(&current) {
+            stats.visited = depths.len();
+            return Some(SearchResult {
+                path: build_path(&nodes, index),
+                cost: depth,
+                stats,
+            });
+        }
+
+        successors(&current).into_iter().for_each(|next| {
+            if let Entry::Vacant(entry) = depths.entry(next.clone()) {
+                entry.insert(depth + 1);
+                queue.push_back((nodes.len(), depth + 1));
+                nodes.push(SearchNode {
+                    state: next,
+                    parent: Some(index),
+                });
+            }
+        });
+        stats.max_frontier = stats.max_frontier.max(queue.len());
+    }
+
+    None
+}
+
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchResult<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    a_star(starts, successors, |_| C::default(), is_goal)
+}
+
+pub fn a_star<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<SearchResult<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut nodes = Vec::new();
+    let mut best = HashMap::<S, (C, usize)>::new();
+    let mut queue = BinaryHeap::new();
+    let mut stats = SearchStats::default();
+
+    starts.into_iter().for_each(|start| {
+        if let Entry::Vacant(entry) = best.entry(start.clone()) {
+            let index = nodes.len();
+            entry.insert((C::default(), index));
+            queue.push(Reverse((heuristic(&start), C::default(), index)));
+            nodes.push(SearchNode {
+                state: start,
+                parent: None,
+            });
+        }
+    });
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        let current = nodes.get(index).expect("Valid index").state.clone();
+        match best.get(&current) {
+            Some((best_cost, best_index)) if *best_cost < cost || *best_index != index => continue,
+            _ => (),
+        }
+        stats.expanded += 1;
+
+        if is_goal(&current) {
+            stats.visited = best.len();
+            return Some(SearchResult {
+                path: build_path(&nodes, index),
+                cost,
+                stats,
+            });
+        }
+
+        successors(&current)
+            .into_iter()
+            .for_each(|(next, step_cost)| {
+                let next_cost = cost + step_cost;
+                let improved = best
+                    .get(&next)
+                    .map(|(known_cost, _)| next_cost < *known_cost)
+                    .unwrap_or(true);
+                if improved {
+                    let next_index = nodes.len();
+                    queue.push(Reverse((
+                        next_cost + heuristic(&next),
+                        next_cost,
+                        next_index,
+                    )));
+                    best.insert(next.clone(), (next_cost, next_index));
+                    nodes.push(SearchNode {
+                        state: next,
+                        parent: Some(index),
+                    });
+                }
+            });
+        stats.max_frontier = stats.max_frontier.max(queue.len());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn successors(node: &&'static str) -> Vec<(&'static str, usize)> {
+        match *node {
+            "A" => vec![("B", 1), ("C", 4), ("F", 1)],
+            "B" => vec![("C", 2), ("D", 5)],
+            "C" => vec![("D", 1)],
+            _ => Vec::new(),
+        }
+    }
+
+    fn heuristic(node: &&'static str) -> usize {
+        match *node {
+            "A" => 3,
+            "B" => 2,
+            "C" => 1,
+            "F" => 10,
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn dijkstra_finds_the_cheapest_path() {
+        let result = dijkstra(["A"], successors, |node| *node == "D").unwrap();
+
+        assert_eq!(result.path, vec!["A", "B", "C", "D"]);
+        assert_eq!(result.cost, 4);
+        assert_eq!(
+            result.stats,
+            SearchStats {
+                visited: 5,
+                expanded: 5,
+                max_frontier: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn a_star_skips_nodes_the_heuristic_rules_out() {
+        let result = a_star(["A"], successors, heuristic, |node| *node == "D").unwrap();
+
+        assert_eq!(result.path, vec!["A", "B", "C", "D"]);
+        assert_eq!(result.cost, 4);
+        assert_eq!(
+            result.stats,
+            SearchStats {
+                visited: 5,
+                expanded: 4,
+                max_frontier: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn searches_report_unreachable_goals() {
+        assert!(dijkstra(["A"], successors, |node| *node == "E").is_none());
+        assert!(a_star(["A"], successors, heuristic, |node| *node == "E").is_none());
+
+        let start = dijkstra(["A"], successors, |node| *node == "A").unwrap();
+        assert_eq!(start.path, vec!["A"]);
+        assert_eq!(start.cost, 0);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::RotationDegrees;
+use std::{
+    collections::{HashSet, VecDeque},
+    hash::Hash,
+    ops::{Add, Neg, Sub},
+};
+
+const FACE_OFFSETS: [(i8, i8, i8); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl<T> Point3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + From<i8>,
+{
+    pub fn new(x: T, y: T, z: T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+
+    pub fn into_iter_face_adjacent(self) -> FaceAdjacentIterator<T> {
+        FaceAdjacentIterator {
+            point: self,
+            index: 0,
+        }
+    }
+
+    pub fn into_iter_all_adjacent(self) -> AllAdjacentIterator<T> {
+        AllAdjacentIterator {
+            point: self,
+            index: 0,
+        }
+    }
+
+    fn offset(&self, (x, y, z): (i8, i8, i8)) -> Point3<T> {
+        Point3 {
+            x: offset_coordinate(self.x, x),
+            y: offset_coordinate(self.y, y),
+            z: offset_coordinate(self.z, z),
+        }
+    }
+}
+
+impl<T> Point3<T>
+where
+    T: Copy + Neg<Output = T>,
+{
+    pub fn rotate(&self, axis: &Axis, rotation: &RotationDegrees) -> Point3<T> {
+        let turns = match rotation {
+            RotationDegrees::Zero => 0,
+            RotationDegrees::Ninety => 1,
+            RotationDegrees::OneHundredEighty => 2,
+            RotationDegrees::TwoHundredSeventy => 3,
+        };
+        (0..turns).fold(*self, |point, _| point.rotate_ninety(axis))
+    }
+
+    fn rotate_ninety(&self, axis: &Axis) -> Point3<T> {
+        match axis {
+            Axis::X => Point3 {
+                x: self.x,
+                y: -self.z,
+                z: self.y,
+            },
+            Axis::Y => Point3 {
+                x: self.z,
+                y: self.y,
+                z: -self.x,
+            },
+            Axis::Z => Point3 {
+                x: -self.y,
+                y: self.x,
+                z: self.z,
+            },
+        }
+    }
+}
+
+impl<T> Add for Point3<T>
+where
+    T: Add<Output = T>,
+{
+    type Output = Point3<T>;
+
+    fn add(self, other: Point3<T>) -> Point3<T> {
+        Point3 {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl<T> Neg for Point3<T>
+where
+    T: Neg<Output = T>,
+{
+    type Output = Point3<T>;
+
+    fn neg(self) -> Point3<T> {
+        Point3 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl<T> Sub for Point3<T>
+where
+    T: Sub<Output = T>,
+{
+    type Output = Point3<T>;
+
+    fn sub(self, other: Point3<T>) -> Point3<T> {
+        Point3 {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+fn offset_coordinate<T>(value: T, delta: i8) -> T
+where
+    T: Add<Output = T> + From<i8>,
+{
+    value + T::from(delta)
+}
+
+pub struct FaceAdjacentIterator<T> {
+    point: Point3<T>,
+    index: usize,
+}
+
+impl<T> Iterator for FaceAdjacentIterator<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + From<i8>,
+{
+    type Item = Point3<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = FACE_OFFSETS.get(self.index)?;
+        self.index += 1;
+        Some(self.point.offset(*offset))
+    }
+}
+
+pub struct AllAdjacentIterator<T> {
+    point: Point3<T>,
+    index: usize,
+}
+
+impl<T> Iterator for AllAdjacentIterator<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + From<i8>,
+{
+    type Item = Point3<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Index 13 of the 3x3x3 cube is the point itself.
+        if self.index == 13 {
+            self.index += 1;
+        }
+        if self.index >= 27 {
+            return None;
+        }
+        let offset = (
+            (self.index / 9) as i8 - 1,
+            (self.index / 3 % 3) as i8 - 1,
+            (self.index % 3) as i8 - 1,
+        );
+        self.index += 1;
+        Some(self.point.offset(offset))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox3<T> {
+    pub min: Point3<T>,
+    pub max: Point3<T>,
+}
+
+impl<T> BoundingBox3<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<i8>,
+{
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Point3<T>>) -> Option<Self>
+    where
+        T: 'a,
+    {
+        points.into_iter().fold(None, |acc, point| match acc {
+            None => Some(BoundingBox3 {
+                min: *point,
+                max: *point,
+            }),
+            Some(bounds) => Some(BoundingBox3 {
+                min: Point3 {
+                    x: bounds.min.x.min(point.x),
+                    y: bounds.min.y.min(point.y),
+                    z: bounds.min.z.min(point.z),
+                },
+                max: Point3 {
+                    x: bounds.max.x.max(point.x),
+                    y: bounds.max.y.max(point.y),
+                    z: bounds.max.z.max(point.z),
+                },
+            }),
+        })
+    }
+
+    pub fn expand(&self, amount: T) -> Self {
+        let delta = Point3 {
+            x: amount,
+            y: amount,
+            z: amount,
+        };
+        BoundingBox3 {
+            min: self.min - delta,
+            max: self.max + delta,
+        }
+    }
+
+    pub fn contains(&self, point: &Point3<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn is_on_boundary(&self, point: &Point3<T>) -> bool {
+        self.contains(point)
+            && (point.x == self.min.x
+                || point.x == self.max.x
+                || point.y == self.min.y
+                || point.y == self.max.y
+                || point.z == self.min.z
+                || point.z == self.max.z)
+    }
+}
+
+pub fn flood_fill_3d<T>(
+    start: Point3<T>,
+    bounds: &BoundingBox3<T>,
+    is_passable: impl Fn(&Point3<T>) -> bool,
+) -> HashSet<Point3<T>>
+where
+    T: Copy + Ord + Hash + Add<Output = T> + Sub<Output = T> + From<i8>,
+{
+    let mut visited = HashSet::new();
+    if !bounds.contains(&start) || !is_passable(&start) {
+        return visited;
+    }
+
+    let mut queue = VecDeque::from([start]);
+    visited.insert(start);
+
+    while let Some(current) = queue.pop_front() {
+        current
+            .into_iter_face_adjacent()
+            .filter(|point| bounds.contains(point) && is_passable(point))
+            .for_each(|point| {
+                if visited.insert(point) {
+                    queue.push_back(point);
+                }
+            });
+    }
+
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjacent_points_surround_the_origin() {
+        let origin = Point3::new(0isize, 0, 0);
+        let faces = origin.into_iter_face_adjacent().collect::<HashSet<_>>();
+        let all = origin.into_iter_all_adjacent().collect::<HashSet<_>>();
+
+        assert_eq!(faces.len(), 6);
+        assert!(faces.contains(&Point3::new(0, -1, 0)));
+        assert_eq!(all.len(), 26);
+        assert!(!all.contains(&origin));
+        assert!(all.contains(&Point3::new(-1, -1, -1)));
+        assert!(all.contains(&Point3::new(1, 1, 1)));
+        assert!(faces.is_subset(&all));
+    }
+
+    #[test]
+    fn rotations_turn_around_each_axis() {
+        let point = Point3::new(1isize, 2, 3);
+
+        assert_eq!(
+            point.rotate(&Axis::X, &RotationDegrees::Ninety),
+            Point3::new(1, -3, 2)
+        );
+        assert_eq!(
+            point.rotate(&Axis::Y, &RotationDegrees::OneHundredEighty),
+            Point3::new(-1, 2, -3)
+        );
+        assert_eq!(
+            point.rotate(&Axis::Z, &RotationDegrees::TwoHundredSeventy),
+            Point3::new(2, -1, 3)
+        );
+        assert_eq!(point.rotate(&Axis::Z, &RotationDegrees::Zero), point);
+        assert_eq!(
+            point
+                .rotate(&Axis::Y, &RotationDegrees::Ninety)
+                .rotate(&Axis::Y, &RotationDegrees::TwoHundredSeventy),
+            point
+        );
+    }
+
+    #[test]
+    fn bounding_box_contains_and_bounds_points() {
+        let points = [Point3::new(1isize, 5, -2), Point3::new(3, 2, 0)];
+        let bounds = BoundingBox3::from_points(&points).unwrap();
+
+        assert_eq!(bounds.min, Point3::new(1, 2, -2));
+        assert_eq!(bounds.max, Point3::new(3, 5, 0));
+        assert!(bounds.contains(&Point3::new(2, 3, -1)));
+        assert!(!bounds.is_on_boundary(&Point3::new(2, 3, -1)));
+        assert!(bounds.is_on_boundary(&Point3::new(3, 3, -1)));
+        assert!(!bounds.is_on_boundary(&Point3::new(4, 3, -1)));
+        assert!(bounds.expand(1).is_on_boundary(&Point3::new(4, 3, -1)));
+        assert_eq!(BoundingBox3::<isize>::from_points(&[]), None);
+    }
+
+    #[test]
+    fn flood_fill_stops_at_walls() {
+        let bounds = BoundingBox3 {
+            min: Point3::new(0isize, 0, 0),
+            max: Point3::new(2, 2, 2),
+        };
+        let filled = flood_fill_3d(Point3::new(0, 0, 0), &bounds, |point| point.x != 1);
+
+        assert_eq!(filled.len(), 9);
+        assert!(filled.iter().all(|point| point.x == 0));
+        assert!(flood_fill_3d(Point3::new(1, 0, 0), &bounds, |point| point.x != 1).is_empty());
+    }
+}
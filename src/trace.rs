@@ -0,0 +1,44 @@
+use anyhow::Result;
+use clap::{builder::PathBufValueParser, Arg, ArgAction, ArgMatches, ValueHint};
+use std::{fs::File, io::stderr, path::PathBuf, sync::Mutex};
+use tracing::Level;
+use tracing_subscriber::fmt::{format::FmtSpan, writer::BoxMakeWriter};
+
+pub fn trace_args() -> Vec<Arg> {
+    vec![
+        Arg::new("trace")
+            .short('v')
+            .long("trace")
+            .help("Traces solver internals, repeat for more detail")
+            .action(ArgAction::Count),
+        Arg::new("trace_file")
+            .long("trace-file")
+            .help("Writes the trace to a file instead of stderr")
+            .num_args(1)
+            .action(ArgAction::Set)
+            .value_hint(ValueHint::FilePath)
+            .value_parser(PathBufValueParser::new()),
+    ]
+}
+
+pub fn init_tracing(args: &ArgMatches) -> Result<()> {
+    let level = match args.get_count("trace") {
+        0 => return Ok(()),
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let file = args.get_one::<PathBuf>("trace_file");
+    let writer = match file {
+        Some(path) => BoxMakeWriter::new(Mutex::new(File::create(path)?)),
+        None => BoxMakeWriter::new(stderr),
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(file.is_none())
+        .with_writer(writer)
+        .init();
+
+    Ok(())
+}
@@ -0,0 +1,451 @@
+use crate::{file_to_string, Command, CommandResult};
+use ansi_to_tui::IntoText;
+use anyhow::{anyhow, Result};
+use clap::Command as ClapCommand;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::{
+    cell::LazyCell,
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+    path::PathBuf,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub type Registry = fn() -> Vec<(&'static str, LazyCell<Box<dyn Command>>)>;
+
+const WORKER_NAME: &str = "tui-worker";
+const TICK: Duration = Duration::from_millis(100);
+const PARTS: [&str; 2] = ["part1", "part2"];
+
+pub fn tui_subcommand() -> ClapCommand {
+    ClapCommand::new("tui")
+        .about("Browses every day and runs their parts from a terminal dashboard")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSource {
+    Input,
+    Sample,
+}
+
+impl InputSource {
+    fn path(&self, day: &str) -> PathBuf {
+        match self {
+            InputSource::Input => PathBuf::from(format!("{}/input.txt", day)),
+            InputSource::Sample => PathBuf::from(format!("{}/sample.txt", day)),
+        }
+    }
+
+    fn toggle(&self) -> InputSource {
+        match self {
+            InputSource::Input => InputSource::Sample,
+            InputSource::Sample => InputSource::Input,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Status {
+    Missing,
+    NotRun,
+    Running,
+    Answer(String, Duration),
+    Error(String, Duration),
+}
+
+impl Status {
+    fn summary(&self) -> String {
+        match self {
+            Status::Missing => "-".to_string(),
+            Status::NotRun => "not run".to_string(),
+            Status::Running => "running".to_string(),
+            Status::Answer(_, elapsed) => format!("{:.2?}", elapsed),
+            Status::Error(_, _) => "error".to_string(),
+        }
+    }
+
+    fn style(&self) -> Style {
+        match self {
+            Status::Missing | Status::NotRun => Style::default().fg(Color::DarkGray),
+            Status::Running => Style::default().fg(Color::Yellow),
+            Status::Answer(_, _) => Style::default().fg(Color::Green),
+            Status::Error(_, _) => Style::default().fg(Color::Red),
+        }
+    }
+}
+
+struct Day {
+    name: &'static str,
+    parts: [(InputSource, Status); 2],
+}
+
+struct Finished {
+    day: usize,
+    part: usize,
+    status: Status,
+}
+
+struct Dashboard {
+    registry: Registry,
+    days: Vec<Day>,
+    selected: ListState,
+    source: InputSource,
+    input_scroll: u16,
+    input: (PathBuf, String),
+    sender: Sender<Finished>,
+    receiver: Receiver<Finished>,
+}
+
+pub fn run_tui(registry: Registry) -> Result<()> {
+    let previous_hook = Arc::<dyn Fn(&PanicHookInfo) + Send + Sync>::from(panic::take_hook());
+    let worker_hook = previous_hook.clone();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() != Some(WORKER_NAME) {
+            worker_hook(info);
+        }
+    }));
+
+    let mut terminal = ratatui::init();
+    let result = Dashboard::new(registry).run(&mut terminal);
+    ratatui::restore();
+    panic::set_hook(Box::new(move |info| previous_hook(info)));
+
+    result
+}
+
+pub fn run_part(registry: Registry, day: &'static str, part: &str, source: InputSource) -> Status {
+    let now = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<CommandResult> {
+        let commands = registry();
+        let (_, command) = commands
+            .iter()
+            .find(|(name, _)| *name == day)
+            .ok_or_else(|| anyhow!("{} is not registered", day))?;
+        let file_contents = file_to_string(&source.path(day))?;
+        let matches = command.get_subcommand().try_get_matches_from([day, part])?;
+        command.run_input(file_contents, &matches)
+    }));
+    let elapsed = now.elapsed();
+
+    match result {
+        Ok(Ok(answer)) => Status::Answer(answer.to_string(), elapsed),
+        Ok(Err(error)) => Status::Error(format!("{:#}", error), elapsed),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Unknown panic".to_string());
+            Status::Error(format!("Panicked: {}", message), elapsed)
+        }
+    }
+}
+
+impl Dashboard {
+    fn new(registry: Registry) -> Dashboard {
+        let days = registry()
+            .iter()
+            .map(|(name, command)| {
+                let subcommand = command.get_subcommand();
+                let status = |part: &str| match subcommand.find_subcommand(part) {
+                    Some(_) => Status::NotRun,
+                    None => Status::Missing,
+                };
+                Day {
+                    name,
+                    parts: [
+                        (InputSource::Input, status(PARTS[0])),
+                        (InputSource::Input, status(PARTS[1])),
+                    ],
+                }
+            })
+            .collect();
+        let (sender, receiver) = channel();
+
+        Dashboard {
+            registry,
+            days,
+            selected: ListState::default().with_selected(Some(0)),
+            source: InputSource::Input,
+            input_scroll: 0,
+            input: (PathBuf::new(), String::new()),
+            sender,
+            receiver,
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if self.collect_finished() {
+                terminal.clear()?;
+            }
+
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    fn selected_day(&self) -> usize {
+        self.selected.selected().unwrap_or(0)
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected.select_previous();
+                self.input_scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected
+                    .select(Some((self.selected_day() + 1).min(self.days.len() - 1)));
+                self.input_scroll = 0;
+            }
+            KeyCode::Char('1') => self.start(self.selected_day(), 0),
+            KeyCode::Char('2') => self.start(self.selected_day(), 1),
+            KeyCode::Char('a') => {
+                (0..PARTS.len()).for_each(|part| self.start(self.selected_day(), part))
+            }
+            KeyCode::Char('s') => {
+                self.source = self.source.toggle();
+                self.input_scroll = 0;
+            }
+            KeyCode::PageDown => self.input_scroll = self.input_scroll.saturating_add(10),
+            KeyCode::PageUp => self.input_scroll = self.input_scroll.saturating_sub(10),
+            _ => (),
+        }
+        true
+    }
+
+    fn start(&mut self, day: usize, part: usize) {
+        let entry = &mut self.days[day].parts[part];
+        if matches!(entry.1, Status::Missing | Status::Running) {
+            return;
+        }
+        *entry = (self.source, Status::Running);
+
+        let registry = self.registry;
+        let name = self.days[day].name;
+        let source = self.source;
+        let sender = self.sender.clone();
+        thread::Builder::new()
+            .name(WORKER_NAME.to_string())
+            .spawn(move || {
+                let status = run_part(registry, name, PARTS[part], source);
+                let _ = sender.send(Finished { day, part, status });
+            })
+            .expect("Thread spawns");
+    }
+
+    fn collect_finished(&mut self) -> bool {
+        self.receiver
+            .try_iter()
+            .map(|finished| self.days[finished.day].parts[finished.part].1 = finished.status)
+            .count()
+            > 0
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list, details] =
+            Layout::horizontal([Constraint::Length(36), Constraint::Min(0)]).areas(main);
+        let [results, input] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(details);
+
+        self.draw_list(frame, list);
+        self.draw_results(frame, results);
+        self.draw_input(frame, input);
+        frame.render_widget(
+            Paragraph::new(
+                "↑/↓ select  1/2 run part  a run both  s toggle input/sample  PgUp/PgDn scroll  q quit",
+            )
+            .style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .days
+            .iter()
+            .map(|day| {
+                let mut spans = vec![Span::raw(format!("{:<7}", day.name))];
+                day.parts.iter().for_each(|(_, status)| {
+                    spans.push(Span::styled(
+                        format!("{:<13}", status.summary()),
+                        status.style(),
+                    ));
+                });
+                ListItem::new(Line::from(spans))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Days"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(list, area, &mut self.selected);
+    }
+
+    fn draw_results(&self, frame: &mut Frame, area: Rect) {
+        let day = &self.days[self.selected_day()];
+        let text = day
+            .parts
+            .iter()
+            .zip(PARTS)
+            .flat_map(|((source, status), part)| {
+                let heading = Line::from(format!("{} on {:?}", part, source)).style(
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .patch(status.style()),
+                );
+                let body = match status {
+                    Status::Missing => Text::from("No preset for this part"),
+                    Status::NotRun => Text::from("Not run yet"),
+                    Status::Running => Text::from("Running..."),
+                    Status::Answer(answer, elapsed) => {
+                        Text::from(format!("{}\nTook {:#?} to run", answer, elapsed))
+                    }
+                    Status::Error(error, elapsed) => {
+                        let mut text = error
+                            .as_bytes()
+                            .into_text()
+                            .unwrap_or_else(|_| Text::from(error.as_str()));
+                        text.push_line(format!("Failed after {:#?}", elapsed));
+                        text
+                    }
+                };
+                [heading]
+                    .into_iter()
+                    .chain(body.lines)
+                    .chain([Line::default()])
+            })
+            .collect::<Vec<_>>();
+
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(day.name)),
+            area,
+        );
+    }
+
+    fn draw_input(&mut self, frame: &mut Frame, area: Rect) {
+        let file = self.source.path(self.days[self.selected_day()].name);
+        if self.input.0 != file {
+            let contents = file_to_string(&file)
+                .unwrap_or_else(|error| format!("Could not read {}: {}", file.display(), error));
+            self.input = (file.clone(), contents);
+        }
+
+        frame.render_widget(
+            Paragraph::new(self.input.1.as_str())
+                .scroll((self.input_scroll, 0))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(file.display().to_string()),
+                ),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_between_blank_lines, parse_lines, parse_usize, ParseError, Problem};
+    use chumsky::{primitive::end, Parser};
+    use clap::Args;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[derive(Debug, Clone, Args)]
+    struct Arguments {
+        #[arg(short, long)]
+        largest: usize,
+    }
+
+    fn parse_file(file: String) -> Result<Vec<Vec<usize>>> {
+        parse_between_blank_lines(parse_lines(parse_usize()))
+            .then_ignore(end())
+            .parse(file.clone())
+            .map_err(|errors| ParseError(file, errors).into())
+    }
+
+    fn run(input: Vec<Vec<usize>>, arguments: Arguments) -> Result<usize> {
+        match arguments.largest {
+            0 => Err(anyhow!("Nothing to sum")),
+            largest => Ok(input.into_iter().flatten().take(largest).sum()),
+        }
+    }
+
+    fn problem() -> Box<dyn Command> {
+        Box::new(
+            Problem::new("day01", "Sums numbers", "Numbers", parse_file, run)
+                .with_part1(Arguments { largest: 2 }, "Sums two numbers")
+                .with_part2(Arguments { largest: 0 }, "Sums nothing"),
+        )
+    }
+
+    fn registry() -> Vec<(&'static str, LazyCell<Box<dyn Command>>)> {
+        vec![("day01", LazyCell::new(problem as fn() -> Box<dyn Command>))]
+    }
+
+    fn wait_for(dashboard: &mut Dashboard, part: usize) {
+        let start = Instant::now();
+        while matches!(dashboard.days[0].parts[part].1, Status::Running) {
+            assert!(start.elapsed() < Duration::from_secs(10), "Part finishes");
+            dashboard.collect_finished();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn render(dashboard: &mut Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn dashboard_runs_parts_on_the_sample() {
+        let mut dashboard = Dashboard::new(registry);
+        assert!(render(&mut dashboard).contains("not run"));
+
+        dashboard.handle_key(KeyCode::Char('s'));
+        dashboard.handle_key(KeyCode::Char('a'));
+        wait_for(&mut dashboard, 0);
+        wait_for(&mut dashboard, 1);
+        let screen = render(&mut dashboard);
+
+        assert!(screen.contains("part1 on Sample"));
+        assert!(screen.contains("3000"));
+        assert!(screen.contains("Nothing to sum"));
+        assert!(screen.contains("day01/sample.txt"));
+        assert!(!dashboard.handle_key(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{measure_allocations, Command};
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction, ArgMatches};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+
+pub fn watch_arg() -> Arg {
+    Arg::new("watch")
+        .long("watch")
+        .help("Re-runs the day every time its input file changes")
+        .num_args(0)
+        .action(ArgAction::SetTrue)
+        .global(true)
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub fn watch(command: &dyn Command, args: &ArgMatches, mut output: impl Write) -> Result<()> {
+    if args.subcommand_name().is_none() && args.get_one::<PathBuf>("file").is_none() {
+        return Err(anyhow!("There is no input file to watch"));
+    }
+    let file = command.input_path(args);
+    let mut last_run = None;
+
+    loop {
+        let current = modified(&file);
+        if current.is_some() && current != last_run {
+            last_run = current;
+            write!(output, "{}", CLEAR_SCREEN)?;
+            writeln!(
+                output,
+                "=============Running {:} on {}=============",
+                command.get_name(),
+                file.display()
+            )?;
+            let now = Instant::now();
+            let (result, allocations) = measure_allocations(|| command.run(args));
+            let elapsed = now.elapsed();
+            match result {
+                Ok(result) => writeln!(output, "{}", result)?,
+                Err(error) => writeln!(output, "Error: {:?}", error)?,
+            }
+            writeln!(output, "Took {:#?} to run", elapsed)?;
+            if let Some(stats) = allocations {
+                writeln!(output, "{}", stats)?;
+            }
+            writeln!(output, "Watching {} for changes", file.display())?;
+            output.flush()?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}